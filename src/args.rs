@@ -1,15 +1,230 @@
 //! Command line argument options and processing
 
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use glob::Pattern;
+
+use crate::{
+    filter::{Filter, Selector},
+    parse::Pagination,
+};
 
 /// Generate well-typed Python HTTP API clients from an OpenAPI specification
 ///
-/// The OpenAPI file is read from `stdin` and written to `stdout`.
+/// By default, the OpenAPI file is read from `stdin` and code is written to
+/// `stdout`.
 #[derive(Parser)]
-pub(crate) enum Args {
-    /// Indicate that `stdin` is formatted as YAML
+// Each of these is an independent command line flag
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct Args {
+    /// Read the OpenAPI document from this file instead of `stdin`
+    #[clap(long, short, value_name = "PATH", global = true)]
+    pub(crate) input: Option<PathBuf>,
+
+    /// Write the generated code to this file instead of `stdout`
+    ///
+    /// With `--package`, this is the directory to write the package to.
+    #[clap(long, short, value_name = "PATH", global = true)]
+    pub(crate) output: Option<PathBuf>,
+
+    /// Generate an installable package directory instead of a single module
+    ///
+    /// The package contains `__init__.py`, `models.py`, `client.py`, a module
+    /// per tag, a `py.typed` marker, and a `pyproject.toml` built from the
+    /// document's title and version.
+    #[clap(long, requires = "output", global = true)]
+    pub(crate) package: bool,
+
+    /// Fail if anything in the document had to be skipped
+    #[clap(long, global = true)]
+    pub(crate) strict: bool,
+
+    /// JSON pointer of a known problem to ignore in strict mode
+    ///
+    /// Everything beneath the pointer is ignored too, so `#/paths/~1legacy`
+    /// allows any problem within the `/legacy` path. May be given multiple
+    /// times.
+    #[clap(long, value_name = "POINTER", global = true)]
+    pub(crate) allow: Vec<String>,
+
+    /// Leave operations marked as deprecated out of the generated code
+    #[clap(long, global = true)]
+    pub(crate) omit_deprecated: bool,
+
+    /// Only generate models for the schemas that operations use
+    ///
+    /// Schemas that are left out are reported.
+    #[clap(long, global = true)]
+    pub(crate) tree_shake: bool,
+
+    /// The query parameter that selects a page of a paginated operation
+    ///
+    /// Operations without an `x-pagination` extension that take this
+    /// parameter, and whose responses have the `--page-next` and
+    /// `--page-items` fields, get an `iter_*` method that yields the items on
+    /// every page.
+    #[clap(
+        long,
+        value_name = "PARAMETER",
+        requires_all = &["page-next", "page-items"],
+        global = true
+    )]
+    pub(crate) page_parameter: Option<String>,
+
+    /// The `.`-separated fields of a response holding the value of
+    /// `--page-parameter` for the next page, like `meta.next_page`
+    #[clap(
+        long,
+        value_name = "FIELDS",
+        requires = "page-parameter",
+        global = true
+    )]
+    pub(crate) page_next: Option<String>,
+
+    /// The `.`-separated fields of a response holding the list of items on a
+    /// page, like `data`
+    #[clap(
+        long,
+        value_name = "FIELDS",
+        requires = "page-parameter",
+        global = true
+    )]
+    pub(crate) page_items: Option<String>,
+
+    /// Only generate operations with this tag
+    ///
+    /// May be given multiple times. When several kinds of `--include-*`
+    /// options are given, operations have to match one of each kind.
+    #[clap(long, value_name = "TAG", global = true)]
+    pub(crate) include_tag: Vec<String>,
+
+    /// Only generate operations whose path matches this glob
+    ///
+    /// `*` matches within a path segment and `**` matches any number of them,
+    /// so `/v2/**` matches every path under `/v2`. May be given multiple
+    /// times.
+    #[clap(long, value_name = "GLOB", global = true)]
+    pub(crate) include_path: Vec<Pattern>,
+
+    /// Only generate operations with this HTTP method
+    ///
+    /// May be given multiple times.
+    #[clap(long, value_name = "METHOD", global = true)]
+    pub(crate) include_method: Vec<String>,
+
+    /// Only generate the operation with this `operationId`
+    ///
+    /// May be given multiple times.
+    #[clap(long, value_name = "ID", global = true)]
+    pub(crate) include_operation: Vec<String>,
+
+    /// Only generate operations that set this specification extension
+    ///
+    /// Operations match if they set the extension, like `x-public`, to
+    /// anything other than `false` or `null`. May be given multiple times.
+    #[clap(long, value_name = "NAME", global = true)]
+    pub(crate) include_extension: Vec<String>,
+
+    /// Leave out operations with this tag
+    ///
+    /// May be given multiple times. Operations matching any `--exclude-*`
+    /// option are left out, even if they're included.
+    #[clap(long, value_name = "TAG", global = true)]
+    pub(crate) exclude_tag: Vec<String>,
+
+    /// Leave out operations whose path matches this glob
+    ///
+    /// May be given multiple times.
+    #[clap(long, value_name = "GLOB", global = true)]
+    pub(crate) exclude_path: Vec<Pattern>,
+
+    /// Leave out operations with this HTTP method
+    ///
+    /// May be given multiple times.
+    #[clap(long, value_name = "METHOD", global = true)]
+    pub(crate) exclude_method: Vec<String>,
+
+    /// Leave out the operation with this `operationId`
+    ///
+    /// May be given multiple times.
+    #[clap(long, value_name = "ID", global = true)]
+    pub(crate) exclude_operation: Vec<String>,
+
+    /// Leave out operations that set this specification extension
+    ///
+    /// For example, `--exclude-extension x-internal` leaves out operations
+    /// with `x-internal: true`. May be given multiple times.
+    #[clap(long, value_name = "NAME", global = true)]
+    pub(crate) exclude_extension: Vec<String>,
+
+    /// How to read the OpenAPI document
+    #[clap(subcommand)]
+    pub(crate) command: Command,
+}
+
+impl Args {
+    /// Which operations code should be generated for
+    pub(crate) fn filter(&self) -> Filter {
+        Filter {
+            omit_deprecated: self.omit_deprecated,
+            include: Selector {
+                tags: self.include_tag.clone(),
+                paths: self.include_path.clone(),
+                methods: self.include_method.clone(),
+                operation_ids: self.include_operation.clone(),
+                extensions: self.include_extension.clone(),
+            },
+            exclude: Selector {
+                tags: self.exclude_tag.clone(),
+                paths: self.exclude_path.clone(),
+                methods: self.exclude_method.clone(),
+                operation_ids: self.exclude_operation.clone(),
+                extensions: self.exclude_extension.clone(),
+            },
+        }
+    }
+
+    /// How to page through operations without an `x-pagination` extension
+    pub(crate) fn pagination(&self) -> Option<Pagination> {
+        match (&self.page_parameter, &self.page_next, &self.page_items) {
+            (Some(parameter), Some(next), Some(items)) => {
+                Some(Pagination::new(parameter, next, items))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Ways an OpenAPI document can be read
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Indicate that the input is formatted as YAML
     Yaml,
 
-    /// Indicate that `stdin` is formatted as JSON
+    /// Indicate that the input is formatted as JSON
     Json,
+
+    /// Detect whether the input is formatted as YAML or JSON
+    ///
+    /// The format is taken from the file extension if there is one, and from
+    /// the contents of the document otherwise.
+    Generate {
+        /// Path or `file://` URL of the OpenAPI document
+        ///
+        /// This takes precedence over `--input`.
+        #[clap(value_name = "PATH", parse(try_from_str = parse_location))]
+        input: Option<PathBuf>,
+    },
+}
+
+/// Convert a path or `file://` URL into a path
+fn parse_location(s: &str) -> Result<PathBuf, String> {
+    if let Some(path) = s.strip_prefix("file://") {
+        Ok(PathBuf::from(path))
+    } else if s.contains("://") {
+        Err("only local paths and `file://` URLs are supported".to_owned())
+    } else {
+        Ok(PathBuf::from(s))
+    }
 }
@@ -0,0 +1,545 @@
+//! Combine an OpenAPI document split across multiple files into one
+//!
+//! `$ref`s to other files are followed relative to the file they appear in.
+//! Schemas they point to, as well as schemas nested elsewhere in the root
+//! document such as in `$defs`, are moved into `components/schemas` of the root
+//! document under a name that doesn't collide with any other schema, and every
+//! other kind of object is copied to where it is referenced.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use heck::ToUpperCamelCase;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::entrypoint::Format;
+
+/// Errors that can occur while following references to other files
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A referenced file couldn't be read
+    #[error("failed to read `{}`: {source}", .path.display())]
+    Read {
+        /// The file that was referenced
+        path: PathBuf,
+
+        /// Why it couldn't be read
+        source: std::io::Error,
+    },
+
+    /// A referenced file isn't valid YAML or JSON
+    #[error("failed to parse `{}`: {message}", .path.display())]
+    Syntax {
+        /// The file that was referenced
+        path: PathBuf,
+
+        /// What the parser had to say about it
+        message: String,
+    },
+
+    /// A reference points to something that doesn't exist
+    #[error("`{0}` doesn't point to anything")]
+    Dangling(String),
+
+    /// A reference to something other than a schema eventually refers back
+    /// to itself, so it can't be copied into place
+    #[error("`{0}` refers to itself")]
+    Cycle(String),
+}
+
+/// A document, identified by its canonical path
+type Document = PathBuf;
+
+/// Something a `$ref` can point to: a document and a JSON pointer into it
+type Target = (Document, String);
+
+/// What kind of object a node in the document is
+///
+/// Only schemas are given names, and data like examples must never have its
+/// contents treated as references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A schema object
+    Schema,
+
+    /// A map whose values are schemas, like `properties`
+    SchemaMap,
+
+    /// A list of schemas, like `allOf`
+    SchemaList,
+
+    /// Some other OpenAPI object, or a map or list of them
+    Other,
+
+    /// Arbitrary user data, like an example value
+    Data,
+}
+
+impl Kind {
+    /// The kind of the value found under `key` in a node of this kind
+    fn child(self, key: &str) -> Self {
+        match self {
+            Self::Schema => match key {
+                "properties" | "patternProperties" | "dependentSchemas"
+                | "$defs" | "definitions" => Self::SchemaMap,
+                "allOf" | "anyOf" | "oneOf" | "prefixItems" => Self::SchemaList,
+                "items"
+                | "additionalProperties"
+                | "not"
+                | "if"
+                | "then"
+                | "else"
+                | "contains"
+                | "propertyNames"
+                | "additionalItems"
+                | "unevaluatedItems"
+                | "unevaluatedProperties" => Self::Schema,
+                _ => Self::Data,
+            },
+            Self::SchemaMap | Self::SchemaList => Self::Schema,
+            Self::Other => match key {
+                "schema" => Self::Schema,
+                "schemas" | "definitions" => Self::SchemaMap,
+                "example" | "value" => Self::Data,
+                _ => Self::Other,
+            },
+            Self::Data => Self::Data,
+        }
+    }
+}
+
+/// State for bundling a single root document
+struct Bundler {
+    /// The root document
+    root: Document,
+
+    /// Every document that has been loaded so far
+    documents: BTreeMap<Document, Value>,
+
+    /// The names given to schemas, by where they came from
+    names: BTreeMap<Target, String>,
+
+    /// Schemas that have been named but not yet copied into the root document
+    pending: Vec<(String, Target)>,
+
+    /// The non-schema references currently being copied into place, used to
+    /// detect cycles
+    copying: Vec<Target>,
+}
+
+/// Bundle the document `root` and every file it refers to into one document
+///
+/// `path` is where `root` was read from, or, if it wasn't read from a file,
+/// a path in the directory its references should be resolved against.
+///
+/// # Errors
+///
+/// This function will fail if a referenced file can't be read or parsed, or if
+/// a reference points to something that doesn't exist.
+pub fn bundle(mut root: Value, path: &Path) -> Result<Value, Error> {
+    let path = canonicalize(path);
+
+    let mut bundler = Bundler {
+        root: path.clone(),
+        documents: BTreeMap::new(),
+        names: BTreeMap::new(),
+        pending: Vec::new(),
+        copying: Vec::new(),
+    };
+
+    // Schemas already in the root document keep their names
+    if let Some(schemas) =
+        root.pointer("/components/schemas").and_then(Value::as_object)
+    {
+        for name in schemas.keys() {
+            bundler.names.insert(
+                (path.clone(), format!("/components/schemas/{}", escape(name))),
+                name.clone(),
+            );
+        }
+    }
+
+    bundler.documents.insert(path.clone(), root.clone());
+    bundler.walk(&mut root, &path, Kind::Other)?;
+
+    let mut schemas = Map::new();
+
+    while let Some((name, target)) = bundler.pending.pop() {
+        let mut schema = bundler.resolve(&target)?;
+        bundler.walk(&mut schema, &target.0, Kind::Schema)?;
+        schemas.insert(name, schema);
+    }
+
+    if !schemas.is_empty() {
+        if let Value::Object(root) = &mut root {
+            let components = root
+                .entry("components")
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            if let Value::Object(components) = components {
+                let existing = components
+                    .entry("schemas")
+                    .or_insert_with(|| Value::Object(Map::new()));
+
+                if let Value::Object(existing) = existing {
+                    existing.append(&mut schemas);
+                }
+            }
+        }
+    }
+
+    Ok(root)
+}
+
+impl Bundler {
+    /// Rewrite the references in `value`, which is of the given `kind` and
+    /// comes from `document`
+    fn walk(
+        &mut self,
+        value: &mut Value,
+        document: &Path,
+        kind: Kind,
+    ) -> Result<(), Error> {
+        if kind == Kind::Data {
+            return Ok(());
+        }
+
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    let reference = reference.clone();
+
+                    return self.reference(value, &reference, document, kind);
+                }
+
+                for (key, child) in object {
+                    self.walk(child, document, kind.child(key))?;
+                }
+            }
+            Value::Array(array) => {
+                // `items` may also be a list of schemas in older drafts
+                let kind = match kind {
+                    Kind::SchemaList | Kind::Schema => Kind::Schema,
+                    other => other,
+                };
+
+                for child in array {
+                    self.walk(child, document, kind)?;
+                }
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite the object `value` containing the `$ref` `reference`
+    fn reference(
+        &mut self,
+        value: &mut Value,
+        reference: &str,
+        document: &Path,
+        kind: Kind,
+    ) -> Result<(), Error> {
+        let (file, pointer) =
+            reference.split_once('#').unwrap_or((reference, ""));
+
+        // References within the root document are left alone, except for
+        // schemas nested somewhere other than `components/schemas`, like in
+        // `$defs`, which need names of their own
+        let component = pointer
+            .strip_prefix("/components/schemas/")
+            .is_some_and(|x| !x.is_empty() && !x.contains('/'));
+
+        if file.is_empty()
+            && document == self.root
+            && (kind != Kind::Schema || component)
+        {
+            return Ok(());
+        }
+
+        let target_document = if file.is_empty() {
+            document.to_owned()
+        } else {
+            canonicalize(
+                &document.parent().unwrap_or_else(|| Path::new(".")).join(file),
+            )
+        };
+
+        let target = (target_document, pointer.to_owned());
+
+        if kind == Kind::Schema {
+            let name = self.name(target);
+
+            if let Value::Object(object) = value {
+                object.insert(
+                    "$ref".to_owned(),
+                    Value::String(format!(
+                        "#/components/schemas/{}",
+                        escape(&name)
+                    )),
+                );
+            }
+
+            return Ok(());
+        }
+
+        if self.copying.contains(&target) {
+            return Err(Error::Cycle(reference.to_owned()));
+        }
+
+        let mut resolved = self.resolve(&target)?;
+
+        self.copying.push(target.clone());
+        self.walk(&mut resolved, &target.0, kind)?;
+        self.copying.pop();
+
+        *value = resolved;
+
+        Ok(())
+    }
+
+    /// Pick the name a schema will have in the bundled document
+    fn name(&mut self, target: Target) -> String {
+        if let Some(name) = self.names.get(&target) {
+            return name.clone();
+        }
+
+        let stem = target
+            .0
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_upper_camel_case())
+            .unwrap_or_default();
+
+        let preferred = target
+            .1
+            .rsplit('/')
+            .next()
+            .filter(|x| !x.is_empty())
+            .map_or_else(|| stem.clone(), unescape);
+
+        let taken: BTreeSet<_> = self.names.values().collect();
+
+        // Disambiguate by the file the schema came from first, since that's
+        // more meaningful than a number
+        let mut name = preferred.clone();
+        let mut suffix = 1;
+
+        while taken.contains(&name) {
+            name = if suffix == 1 {
+                format!("{stem}{preferred}")
+            } else {
+                format!("{stem}{preferred}{suffix}")
+            };
+            suffix += 1;
+        }
+
+        self.names.insert(target.clone(), name.clone());
+        self.pending.push((name.clone(), target));
+
+        name
+    }
+
+    /// Find the value a target points to
+    fn resolve(&mut self, target: &Target) -> Result<Value, Error> {
+        let (document, pointer) = target;
+
+        if !self.documents.contains_key(document) {
+            let loaded = load(document)?;
+            self.documents.insert(document.clone(), loaded);
+        }
+
+        self.documents
+            .get(document)
+            .and_then(|x| x.pointer(pointer))
+            .cloned()
+            .ok_or_else(|| {
+                Error::Dangling(format!("{}#{pointer}", document.display()))
+            })
+    }
+}
+
+/// Read and parse a referenced file
+fn load(path: &Path) -> Result<Value, Error> {
+    let s = std::fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    let format =
+        Format::from_extension(path).unwrap_or_else(|| Format::detect(&s));
+
+    let result = match format {
+        Format::Yaml => serde_yaml::from_str(&s).map_err(|x| x.to_string()),
+        Format::Json => serde_json::from_str(&s).map_err(|x| x.to_string()),
+    };
+
+    result.map_err(|message| Error::Syntax {
+        path: path.to_owned(),
+        message,
+    })
+}
+
+/// Make a path absolute and canonical so the same file is always identified
+/// the same way, falling back to the path itself if it doesn't exist
+fn canonicalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Escape a JSON pointer segment
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// Unescape a JSON pointer segment
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use serde_json::{json, Value};
+
+    use super::{bundle, Error};
+
+    /// Write `files` into a fresh directory named after `test`, returning the
+    /// path of the root document in it
+    fn files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("openapi_codegen-{}-{test}", std::process::id()));
+
+        // Left over from an earlier run, if any
+        fs::remove_dir_all(&directory).ok();
+
+        for (path, contents) in files {
+            let path = directory.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap_or_else(|e| panic!("{e}"));
+            }
+            fs::write(path, contents).unwrap_or_else(|e| panic!("{e}"));
+        }
+
+        directory.join("openapi.yaml")
+    }
+
+    /// Bundle the root document written by [`files`]
+    fn bundled(root: &Value, path: &Path) -> Result<Value, Error> {
+        let result = bundle(root.clone(), path);
+
+        if let Some(directory) = path.parent() {
+            fs::remove_dir_all(directory).ok();
+        }
+
+        result
+    }
+
+    /// Schemas from other files are moved into `components/schemas`, and
+    /// other objects are copied to where they're referenced
+    #[test]
+    fn bundles_external_references() {
+        let path = files(
+            "external",
+            &[
+                ("openapi.yaml", ""),
+                (
+                    "common.yaml",
+                    "Limit:\n  name: limit\n  in: query\n  schema:\n    $ref: \
+                     '#/Count'\nCount:\n  type: integer\n",
+                ),
+            ],
+        );
+        let root = json!({
+            "paths": {
+                "/items": {
+                    "get": {
+                        "parameters": [{ "$ref": "common.yaml#/Limit" }],
+                    },
+                },
+            },
+        });
+
+        let bundled = bundled(&root, &path).unwrap_or_else(|e| panic!("{e}"));
+
+        assert_eq!(
+            bundled["paths"]["/items"]["get"]["parameters"][0],
+            json!({
+                "name": "limit",
+                "in": "query",
+                "schema": { "$ref": "#/components/schemas/Count" },
+            })
+        );
+        assert_eq!(
+            bundled["components"]["schemas"],
+            json!({ "Count": { "type": "integer" } })
+        );
+    }
+
+    /// Schemas whose names are taken are prefixed with the name of their file,
+    /// then numbered
+    #[test]
+    fn renames_colliding_schemas() {
+        let path = files(
+            "colliding",
+            &[
+                ("openapi.yaml", ""),
+                ("pets.yaml", "Pet:\n  type: string\n"),
+                ("other/pets.yaml", "Pet:\n  type: integer\n"),
+            ],
+        );
+        let root = json!({
+            "components": {
+                "schemas": {
+                    "Pet": { "type": "boolean" },
+                    "Pair": {
+                        "type": "object",
+                        "properties": {
+                            "a": { "$ref": "pets.yaml#/Pet" },
+                            "b": { "$ref": "other/pets.yaml#/Pet" },
+                            "c": { "$ref": "pets.yaml#/Pet" },
+                        },
+                    },
+                },
+            },
+        });
+
+        let bundled = bundled(&root, &path).unwrap_or_else(|e| panic!("{e}"));
+        let schemas = &bundled["components"]["schemas"];
+
+        assert_eq!(schemas["Pet"], json!({ "type": "boolean" }));
+        assert_eq!(schemas["PetsPet"], json!({ "type": "string" }));
+        assert_eq!(schemas["PetsPet2"], json!({ "type": "integer" }));
+        assert_eq!(
+            schemas["Pair"]["properties"],
+            json!({
+                "a": { "$ref": "#/components/schemas/PetsPet" },
+                "b": { "$ref": "#/components/schemas/PetsPet2" },
+                "c": { "$ref": "#/components/schemas/PetsPet" },
+            })
+        );
+    }
+
+    /// Objects other than schemas that refer to themselves can't be copied
+    #[test]
+    fn rejects_cycles() {
+        let path = files(
+            "cycle",
+            &[
+                ("openapi.yaml", ""),
+                ("common.yaml", "Loop:\n  $ref: '#/Loop'\n"),
+            ],
+        );
+        let root = json!({
+            "paths": { "/items": { "$ref": "common.yaml#/Loop" } },
+        });
+
+        assert!(matches!(bundled(&root, &path), Err(Error::Cycle(_))));
+    }
+}
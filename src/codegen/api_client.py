@@ -1,6 +1,63 @@
+_IDEMPOTENT_METHODS = {"GET", "HEAD", "OPTIONS", "PUT", "DELETE", "TRACE"}
+
+_RETRY_STATUSES = {429, 503}
+
+_UNSET: Any = object()
+"""The default of arguments where `None` already means something"""
+
+
+def _retry_after(resp: aiohttp.ClientResponse) -> Optional[float]:
+    """
+    Parse the `Retry-After` header of a response into seconds, if it has one
+    """
+
+    value = resp.headers.get("Retry-After")
+
+    if value is None:
+        return None
+
+    try:
+        return max(0.0, float(value))
+    except ValueError:
+        pass
+
+    try:
+        date = parsedate_to_datetime(value)
+    except (TypeError, ValueError):
+        return None
+
+    if date.tzinfo is None:
+        date = date.replace(tzinfo=timezone.utc)
+
+    return max(0.0, (date - datetime.now(timezone.utc)).total_seconds())
+
+
 class ApiClient:
     """
     The HTTP API client
+
+    Requests that are safe to repeat are retried when the connection fails or
+    the server responds with 429 or 503, waiting as long as its `Retry-After`
+    header asks, or with exponential backoff otherwise. These are requests with
+    idempotent methods like `GET` and `PUT`, and requests with an
+    `Idempotency-Key` header. The `timeout`, `max_retries`, `backoff_factor`,
+    and `max_backoff` arguments apply to every request, and can be overridden
+    for a single request with its `request_options` argument. A `timeout` of
+    `None` waits forever, and leaving it out keeps the session's timeout.
+
+    Every request goes through `_request`, so behavior like logging, metrics,
+    tracing headers, or request signing can be added by appending to
+    `before_request_hooks` and `after_response_hooks`, or by overriding
+    `_request` in a subclass and calling `super()._request`.
+
+    Without a `session`, the client creates one when it sends its first
+    request, using `connector` if given, or a connector limited to `limit`
+    connections in total and `limit_per_host` connections to each host
+    otherwise (`0` means no limit). Only sessions the client creates are
+    closed by `aclose`, which is called when leaving `async with`:
+
+        async with ApiClient(base_url, auth) as client:
+            ...
     """
 
     def __init__(
@@ -8,11 +65,152 @@ class ApiClient:
         base_url: str,
         auth: aiohttp.BasicAuth,
         session: Optional[aiohttp.ClientSession] = None,
+        connector: Optional[aiohttp.BaseConnector] = None,
+        limit: int = 100,
+        limit_per_host: int = 0,
+        timeout: Optional[float] = _UNSET,
+        max_retries: int = 2,
+        backoff_factor: float = 0.5,
+        max_backoff: float = 60.0,
+        before_request_hooks: Optional[List[BeforeRequestHook]] = None,
+        after_response_hooks: Optional[List[AfterResponseHook]] = None,
     ) -> None:
         self._base_url = base_url
         self._auth = auth
+        self._request_options: RequestOptions = {
+            "max_retries": max_retries,
+            "backoff_factor": backoff_factor,
+            "max_backoff": max_backoff,
+        }
+
+        if timeout is not _UNSET:
+            self._request_options["timeout"] = timeout
+        self.before_request_hooks: List[BeforeRequestHook] = list(
+            before_request_hooks or []
+        )
+        self.after_response_hooks: List[AfterResponseHook] = list(
+            after_response_hooks or []
+        )
+
+        self._session = session
+        self._owns_session = session is None
+        self._connector = connector
+        self._limit = limit
+        self._limit_per_host = limit_per_host
+
+    async def __aenter__(self) -> "ApiClient":
+        return self
+
+    async def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc: Optional[BaseException],
+        traceback: Optional[TracebackType],
+    ) -> None:
+        await self.aclose()
+
+    async def aclose(self) -> None:
+        """
+        Close the session, if the client created it
+
+        A session passed to the client is left open for its owner to close. A
+        connector passed to the client is left open too.
+        """
+
+        if self._owns_session and self._session is not None:
+            await self._session.close()
+            self._session = None
+
+    def _get_session(self) -> aiohttp.ClientSession:
+        """
+        Get the session to send requests with, creating it if there isn't one
+        """
+
+        if self._session is None:
+            connector = self._connector or aiohttp.TCPConnector(
+                limit=self._limit,
+                limit_per_host=self._limit_per_host,
+            )
+
+            self._session = aiohttp.ClientSession(
+                connector=connector,
+                connector_owner=self._connector is None,
+            )
+
+        return self._session
+
+    async def _request(
+        self,
+        method: str,
+        url: str,
+        request_options: Optional[RequestOptions] = None,
+        **kwargs: Any,
+    ) -> aiohttp.ClientResponse:
+        """
+        Send a request, retrying it when that's safe
+
+        Keyword arguments are passed on to `aiohttp.ClientSession.request`,
+        after going through `before_request_hooks` in order. Each response goes
+        through `after_response_hooks` in order before it's returned or retried.
+        """
+
+        options: RequestOptions = {
+            **self._request_options,
+            **(request_options or {}),
+        }
+
+        # A total of `None` turns off aiohttp's default timeout
+        if "timeout" in options:
+            kwargs["timeout"] = aiohttp.ClientTimeout(total=options["timeout"])
+
+        headers = kwargs.get("headers") or {}
+        retryable = method.upper() in _IDEMPOTENT_METHODS or any(
+            x.lower() == "idempotency-key" for x in headers
+        )
+
+        max_retries = options.get("max_retries", 0)
+        backoff_factor = options.get("backoff_factor", 0.0)
+        max_backoff = options.get("max_backoff", 0.0)
+
+        attempt = 0
+
+        while True:
+            backoff = min(max_backoff, backoff_factor * 2**attempt)
+            can_retry = retryable and attempt < max_retries
+
+            # Hooks see a fresh copy for each attempt, so changes don't pile up
+            attempt_kwargs: Dict[str, Any] = {
+                **kwargs,
+                "headers": dict(headers),
+            }
+
+            for before_request in self.before_request_hooks:
+                await before_request(method, url, attempt_kwargs)
+
+            try:
+                resp = await self._get_session().request(
+                    method, url, **attempt_kwargs
+                )
+            except (aiohttp.ClientConnectionError, asyncio.TimeoutError):
+                if not can_retry:
+                    raise
+
+                delay = backoff
+            else:
+                for after_response in self.after_response_hooks:
+                    await after_response(resp)
+
+                if resp.status not in _RETRY_STATUSES or not can_retry:
+                    return resp
+
+                retry_after = _retry_after(resp)
+
+                # Waiting longer than allowed would be no better than failing
+                if retry_after is not None and retry_after > max_backoff:
+                    return resp
+
+                delay = backoff if retry_after is None else retry_after
+                resp.release()
 
-        if session:
-            self._session = session
-        else:
-            self._session = aiohttp.ClientSession()
+            await asyncio.sleep(delay)
+            attempt += 1
@@ -0,0 +1,3 @@
+class _BaseModel(BaseModel):
+    class Config:
+        allow_population_by_field_name = True
@@ -1,29 +1,83 @@
 //! Generate code for HTTP methods
 
-use std::{borrow::Borrow, collections::BTreeMap};
+use std::collections::BTreeMap;
 
-use heck::ToSnakeCase;
-use okapi::openapi3::OpenApi;
+use heck::{ToSnakeCase, ToUpperCamelCase};
 
-use super::type_to_string;
-use crate::parse::{Argument, Function, Location, SecurityScheme, Type};
+use super::{
+    docstring, identifier, is_mutable, literal, string_literal, type_to_string,
+};
+use crate::parse::{
+    Argument, Function, Location, Pagination, PathSegment, SecurityScheme,
+    Style, Type,
+};
 
-/// Generates a function for each method available on each HTTP path
+/// Generates a method for each of the given functions
 // TODO: remove this when more HTTP auth methods are implemented
 #[allow(clippy::zero_sized_map_values)]
-pub fn functions(
-    openapi: &OpenApi,
+pub fn functions<'a, I>(
+    fs: I,
     security_schemes: &BTreeMap<String, SecurityScheme>,
-) -> String {
-    let fs = Function::try_from_paths(&openapi.paths).expect("problems");
-
+) -> String
+where
+    I: IntoIterator<Item = (&'a (String, String), &'a Function)>,
+{
     let mut code = String::new();
 
     for ((method, path), function) in fs {
-        code.push_str(&signature(1, &method, &path, &function));
-        code.push_str(&documentation(2, function.docs.as_ref()));
-        code.push_str(&body(2, method, path, &function, security_schemes));
+        let name = name(method, path);
+        let raw = raw_return_type(method, path, function);
+
+        // Both public methods send the request through the same private one,
+        // and only differ in what they return
+        let send = format!(
+            "self._{name}({})",
+            forwarded_arguments(&function.arguments)
+        );
+
+        code.push_str(&signature(
+            1,
+            &name,
+            function,
+            &return_type(&function.responses).0,
+        ));
+        code.push_str(&documentation(2, function));
+        code.push_str(&deprecations(2, method, path, function));
+        code.push_str(&format!(
+            "{i}return (await {send}).body\n\n",
+            i = indents(2)
+        ));
+
+        code.push_str(&signature(
+            1,
+            &format!("{name}_with_raw_response"),
+            function,
+            &raw,
+        ));
+        code.push_str(&docstring(
+            &format!(
+                "Like `{name}`, but also return the status and headers of the \
+                 response\n\nSee `{name}` for the arguments and responses."
+            ),
+            &indents(2),
+        ));
+        code.push('\n');
+        code.push_str(&deprecations(2, method, path, function));
+        code.push_str(&format!("{i}return await {send}\n\n", i = indents(2)));
+
+        code.push_str(&signature(1, &format!("_{name}"), function, &raw));
+        code.push_str(&docstring(
+            &format!("Send the request of `{name}`"),
+            &indents(2),
+        ));
+        code.push('\n');
+        code.push_str(&body(2, method, path, function, security_schemes));
         code.push_str("\n\n");
+
+        if let Some(pagination) = &function.pagination {
+            code.push_str(&iterator(1, method, path, function, pagination));
+            code.push_str("\n\n");
+        }
     }
 
     code
@@ -31,7 +85,7 @@ pub fn functions(
 
 /// Generate an amount of indentations
 fn indents(indent_level: usize) -> String {
-    (0..indent_level).into_iter().fold(String::new(), |mut acc, _| {
+    (0..indent_level).fold(String::new(), |mut acc, _| {
         acc.push_str(super::INDENT);
         acc
     })
@@ -55,21 +109,21 @@ fn query_param_arguments(
     let mut code = String::new();
 
     code.push_str(&format!(
-        "{i}init_params: List[Tuple[str, Optional[str]]] = [\n",
+        "{i}params: List[Tuple[str, str]] = [\n",
         i = indents(indent_level)
     ));
 
-    // Generate a list of trivially convertable parameters
-    function
-        .arguments
-        .iter()
-        .filter(|x| x.location == Location::Query)
-        .filter_map(|x| {
-            var_to_url_str(&x.name, &x.r#type).map(|s| (&x.name, s))
-        })
-        .for_each(|(name, stringifier)| {
+    // Each parameter expands to zero or more key-value pairs depending on its
+    // value and serialization style
+    argument_names(&function.arguments)
+        .into_iter()
+        .filter(|(_, x)| x.location == Location::Query)
+        .for_each(|(name, x)| {
             code.push_str(&format!(
-                "{i}(\"{name}\", {stringifier}),\n",
+                "{i}*_serialize_query_param({}, {name}, \"{}\", {}),\n",
+                string_literal(&x.name),
+                x.style.as_str(),
+                python_bool(x.explode),
                 i = indents(indent_level + 1)
             ));
         });
@@ -77,109 +131,127 @@ fn query_param_arguments(
     // Close off the list
     code.push_str(&format!("{i}]\n", i = indents(indent_level)));
 
-    // Omit any `None` values
+    code.push('\n');
+
+    Some((code, "params=params, "))
+}
+
+/// Generate the headers of the HTTP request from the header arguments
+///
+/// The first string in the tuple is the code that builds the headers, and the
+/// second string is what should be included in the HTTP call.
+fn header_arguments(
+    indent_level: usize,
+    function: &Function,
+) -> Option<(String, &'static str)> {
+    let has_header_arguments =
+        function.arguments.iter().any(|x| x.location == Location::Header);
+
+    if !has_header_arguments {
+        return None;
+    }
+
+    let has_body =
+        function.arguments.iter().any(|x| x.location == Location::Body);
+
+    let mut code = String::new();
+
     code.push_str(&format!(
-        "{i}params: List[Tuple[str, str]] = [(k, v) for k, v in init_params \
-         if v is not None]\n",
-        i = indents(indent_level),
+        "{i}headers: Dict[str, str] = {{\n",
+        i = indents(indent_level)
     ));
 
-    // Convert lists and optional lists into tuple pairs
-    //
-    // I swear, if some OpenAPI spec takes a *list* of *options*...
-    function
-        .arguments
-        .iter()
-        .filter(|x| x.location == Location::Query)
-        .filter_map(
-            // TODO: This is probably fine, but ideally it would recurse. If it
-            // does recurse, the `if {name} is not None` check below should
-            // technically also recurse, but since `Option[T]` is the same as
-            // `Union[T, None]` and `Union[Union[T, None] None]` is technically
-            // the same, it probably doesn't actually matter.
-            |x| match &x.r#type {
-                Type::List(y) | Type::Set(y) => Some((&x.name, &x.r#type, y)),
-                Type::Option(y) => match y.borrow() {
-                    Type::List(y) | Type::Set(y) => {
-                        Some((&x.name, &x.r#type, y))
-                    }
-                    _ => None,
-                },
-                _ => None,
-            },
-        )
-        .filter_map(|(x, original_ty, list_item_ty)| {
-            var_to_url_str("x", list_item_ty).map(move |s| (x, original_ty, s))
-        })
-        .for_each(|(name, ty, stringifier)| {
-            if matches!(ty.borrow(), Type::Option(_)) {
-                code.push_str(&format!(
-                    "{i}if {name} is not None:\n",
-                    i = indents(indent_level)
-                ));
-            }
+    // See `body` for why this is sent without a body
+    if !has_body {
+        code.push_str(&format!(
+            "{i}\"Content-Type\": \"application/json\",\n",
+            i = indents(indent_level + 1)
+        ));
+    }
 
+    // Missing headers are left out entirely
+    argument_names(&function.arguments)
+        .into_iter()
+        .filter(|(_, x)| x.location == Location::Header)
+        .for_each(|(name, x)| {
             code.push_str(&format!(
-                "{i}params.extend([(\"{name}\", {}) for x in {name}])\n",
-                stringifier,
-                name = name,
-                i = indents(
-                    indent_level
-                        + if matches!(ty.borrow(), Type::Option(_)) {
-                            1
-                        } else {
-                            0
-                        }
-                ),
+                "{i}**_serialize_header_param({}, {name}, {}),\n",
+                string_literal(&x.name),
+                python_bool(x.explode),
+                i = indents(indent_level + 1)
             ));
         });
 
+    code.push_str(&format!("{i}}}\n", i = indents(indent_level)));
+
     code.push('\n');
 
-    Some((code, "params=params, "))
+    Some((code, "headers=headers, "))
 }
 
-/// From a variable, construct conversion code for turning it into a URL string
+/// Convert a boolean into its Python spelling
+fn python_bool(x: bool) -> &'static str {
+    if x {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+/// Generate the expression that builds the URL for a request
 ///
-/// If this function returns `None`, there is no trivial conversion method.
-fn var_to_url_str<S: AsRef<str>>(name: S, ty: &Type) -> Option<String> {
-    let name = name.as_ref();
-
-    match ty {
-        // Trivial string conversion
-        Type::String | Type::Float | Type::Integer => {
-            Some(format!("str({name})"))
+/// Path arguments are percent-encoded so that values containing characters
+/// like `/` or `?` can't change the meaning of the URL.
+fn url(function: &Function) -> String {
+    let names = argument_names(&function.arguments);
+    let mut url = String::from("self._base_url");
+
+    for segment in &function.path {
+        url.push_str(" + ");
+
+        match segment {
+            PathSegment::Literal(x) => url.push_str(&string_literal(x)),
+            PathSegment::Parameter(name) => {
+                // Parsing guarantees that a matching path argument exists, but
+                // fall back to the defaults just in case
+                let (identifier, style, explode) = names
+                    .iter()
+                    .find(|(_, x)| {
+                        x.location == Location::Path && &x.name == name
+                    })
+                    .map_or_else(
+                        || (identifier(name), Style::Simple, false),
+                        |(identifier, x)| {
+                            (identifier.clone(), x.style, x.explode)
+                        },
+                    );
+
+                url.push_str(&format!(
+                    "_serialize_path_param({}, {identifier}, \"{}\", {})",
+                    string_literal(name),
+                    style.as_str(),
+                    python_bool(explode),
+                ));
+            }
         }
-
-        // Use the more typical lowercase versions
-        Type::Bool => Some(format!(r#"("true" if {} else "false")"#, name)),
-
-        // Handle optional values properly
-        Type::Option(ty) => Some(format!(
-            "({} if {name} is not None else None)",
-            var_to_url_str(name, ty)?,
-        )),
-
-        _ => None,
     }
+
+    url
 }
 
-/// Generate the body of a function
+/// Generate the body of the private function that sends a request
+///
+/// This returns a `RawResponse`, which the public functions unwrap as needed.
 // TODO: remove this when more HTTP auth methods are implemented
 #[allow(clippy::zero_sized_map_values)]
-fn body<S1, S2>(
+fn body(
     indent_level: usize,
-    method: S1,
-    path: S2,
+    method: &str,
+    path: &str,
     function: &Function,
     security_schemes: &BTreeMap<String, SecurityScheme>,
-) -> String
-where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
-{
-    let method = method.as_ref();
-    let path = path.as_ref();
+) -> String {
+    let url = url(function);
 
     // A list of methods this request can be authenticated by
     let mut schemes = function
@@ -209,23 +281,34 @@ where
         if function.arguments.iter().any(|x| x.location == Location::Body) {
             "json=body.dict(by_alias=True), "
         } else {
-            // As far as I can tell, the default value for requests'
-            // `Content-Type`s is undefined where `requestBody` is undefined.
-            // Some servers require a particular `Content-Type` even though
-            // there is no actual data in the body, so this information is not
-            // conveyed by the OpenAPI spec they release. I think
-            // `application/json` is a relatively safe default that should work
-            // in most cases where this happens. I should probably open an issue
-            // against the OpenAPI spec to sort out this potential ambiguity and
-            // the providers who exhibit this behavior since this might be
-            // non-compliant with whatever the real behavior is supposed to be.
-            r#"headers={"Content-Type": "application/json"}"#
+            ""
         };
 
+    let header_args = if let Some((builder, args)) =
+        header_arguments(indent_level, function)
+    {
+        code.push_str(&builder);
+        args
+    } else if body_args.is_empty() {
+        // As far as I can tell, the default value for requests'
+        // `Content-Type`s is undefined where `requestBody` is undefined. Some
+        // servers require a particular `Content-Type` even though there is no
+        // actual data in the body, so this information is not conveyed by the
+        // OpenAPI spec they release. I think `application/json` is a
+        // relatively safe default that should work in most cases where this
+        // happens. I should probably open an issue against the OpenAPI spec to
+        // sort out this potential ambiguity and the providers who exhibit this
+        // behavior since this might be non-compliant with whatever the real
+        // behavior is supposed to be.
+        r#"headers={"Content-Type": "application/json"}, "#
+    } else {
+        ""
+    };
+
     code.push_str(&format!(
-        "{i}resp = await \
-         self._session.{method}(f\"{{self._base_url}}{path}\", \
-         {auth_args}{param_args}{body_args})\n",
+        "{i}resp = await self._request(\"{}\", {url}, request_options, \
+         {auth_args}{param_args}{body_args}{header_args})\n",
+        method.to_uppercase(),
         i = indents(indent_level),
     ));
 
@@ -242,21 +325,23 @@ where
 
         let (_code, rets) = return_type(&function.responses);
 
-        let body = match rets {
+        let value = match rets {
             Return::Many => format!(
-                "{i}return (\"{ty}\", parse_obj_as({ty}, await resp.json()))\n",
+                "(\"{ty}\", parse_obj_as({ty}, await resp.json()))",
                 ty = type_to_string(ty, false),
-                i = indents(indent_level + 1)
             ),
             Return::One => format!(
-                "{i}return parse_obj_as({ty}, await resp.json())\n",
+                "parse_obj_as({ty}, await resp.json())",
                 ty = type_to_string(ty, false),
-                i = indents(indent_level + 1)
             ),
         };
 
         code.push_str(&cond);
-        code.push_str(&body);
+        code.push_str(&format!(
+            "{i}return {}\n",
+            raw_response(method, path, function, &value),
+            i = indents(indent_level + 1)
+        ));
     });
 
     if function.responses.is_empty() {
@@ -264,6 +349,12 @@ where
             "\n{i}resp.raise_for_status()",
             i = indents(indent_level)
         ));
+
+        code.push_str(&format!(
+            "\n{i}return {}",
+            raw_response(method, path, function, "None"),
+            i = indents(indent_level)
+        ));
     } else {
         code.push_str(&format!(
             "\n{i}raise aiohttp.ClientResponseError(resp.request_info, \
@@ -275,48 +366,254 @@ where
     code
 }
 
+/// Generate the expression that wraps the value a function returns in a
+/// `RawResponse`
+///
+/// The headers are parsed when they're first used, so that invalid headers
+/// only cause errors for callers that look at them.
+fn raw_response(
+    method: &str,
+    path: &str,
+    function: &Function,
+    value: &str,
+) -> String {
+    let headers = if function.response_headers.is_some() {
+        format!(
+            "lambda: _parse_headers({}, resp.headers)",
+            header_model(method, path)
+        )
+    } else {
+        "lambda: None".to_owned()
+    };
+
+    format!("RawResponse(resp, {value}, {headers})")
+}
+
 /// Generate the documentation for a function
-fn documentation<S: AsRef<str>>(
+///
+/// This is in the Google style, with sections for the arguments and the
+/// responses that can be returned.
+fn documentation(indent_level: usize, function: &Function) -> String {
+    // Lines after the first in each entry of a section are indented further
+    let hanging = format!("\n{}", indents(2));
+
+    let mut sections: Vec<String> = function.docs.iter().cloned().collect();
+
+    let arguments: Vec<_> = argument_names(&function.arguments)
+        .into_iter()
+        .filter_map(|(name, x)| Some((name, x.docs.as_deref()?.trim())))
+        .filter(|(_, docs)| !docs.is_empty())
+        .collect();
+
+    if !arguments.is_empty() {
+        let mut section = String::from("Args:");
+
+        for (name, docs) in arguments {
+            section.push_str(&format!(
+                "\n{}{name}: {}",
+                super::INDENT,
+                docs.replace('\n', &hanging)
+            ));
+        }
+
+        sections.push(section);
+    }
+
+    if !function.response_docs.is_empty() {
+        let mut section = String::from("Returns:");
+
+        for (code, docs) in &function.response_docs {
+            section.push_str(&format!(
+                "\n{}{code}: {}",
+                super::INDENT,
+                docs.replace('\n', &hanging)
+            ));
+        }
+
+        sections.push(section);
+    }
+
+    let mut code = docstring(&sections.join("\n\n"), &indents(indent_level));
+    code.push('\n');
+
+    code
+}
+
+/// Generate the warnings raised when a deprecated function or argument is used
+///
+/// Deprecated optional arguments always default to `None` so that passing them
+/// can be told apart from leaving them out.
+fn deprecations(
     indent_level: usize,
-    docs: Option<S>,
+    method: &str,
+    path: &str,
+    function: &Function,
 ) -> String {
-    match docs {
-        Some(x) => {
-            format!(
-                "{i}\"\"\"\n{}\n{i}\"\"\"\n",
-                x.as_ref(),
-                i = indents(indent_level)
-            )
+    let name = name(method, path);
+    let warn = |message: String| {
+        format!(
+            "warnings.warn({}, DeprecationWarning, stacklevel=2)\n",
+            string_literal(&message)
+        )
+    };
+
+    let mut code = String::new();
+
+    if function.deprecated {
+        code.push_str(&indents(indent_level));
+        code.push_str(&warn(format!("`{name}` is deprecated")));
+    }
+
+    for (identifier, argument) in argument_names(&function.arguments)
+        .into_iter()
+        .filter(|(_, x)| x.deprecated)
+    {
+        let message =
+            format!("the `{identifier}` argument of `{name}` is deprecated");
+
+        if argument.required {
+            code.push_str(&indents(indent_level));
+        } else {
+            code.push_str(&format!(
+                "{}if {identifier} is not None:\n{}",
+                indents(indent_level),
+                indents(indent_level + 1)
+            ));
         }
-        None => format!("{i}\"\"\"\n{i}\"\"\"\n", i = indents(indent_level)),
+
+        code.push_str(&warn(message));
+    }
+
+    if !code.is_empty() {
+        code.push('\n');
     }
+
+    code
 }
 
 /// Generate an entire function signature
 ///
 /// For example, `async def get_foo_foo_id(foo_id: str) -> Foo:`. This includes
 /// the requested indentation and a single trailing newline.
-fn signature<S1, S2>(
+fn signature(
     indent_level: usize,
-    method: S1,
-    path: S2,
+    name: &str,
     function: &Function,
-) -> String
-where
-    S1: AsRef<str>,
-    S2: AsRef<str>,
-{
+    return_type: &str,
+) -> String {
     format!(
-        "{i}async def {}(self, {}) -> {}:\n",
-        name(method, path),
+        "{i}async def {name}(self, {}) -> {return_type}:\n",
         arguments(&function.arguments),
-        return_type(&function.responses).0,
         i = indents(indent_level),
     )
 }
 
+/// Generate the type returned by the methods returning a `RawResponse`, like
+/// `get_foo_foo_id_with_raw_response`
+fn raw_return_type(method: &str, path: &str, function: &Function) -> String {
+    let headers = if function.response_headers.is_some() {
+        header_model(method, path)
+    } else {
+        "None".to_owned()
+    };
+
+    format!("RawResponse[{}, {headers}]", return_type(&function.responses).0)
+}
+
+/// Generate the arguments that pass along every argument of a function, along
+/// with its request options
+///
+/// Every argument is passed by name, since required arguments don't always
+/// come first.
+fn forwarded_arguments(arguments: &[Argument]) -> String {
+    let mut forwarded = argument_names(arguments).into_iter().fold(
+        String::new(),
+        |mut acc, (x, _)| {
+            acc.push_str(&format!("{x}={x}, "));
+            acc
+        },
+    );
+    forwarded.push_str("request_options=request_options, ");
+
+    forwarded
+}
+
+/// Generate an async generator that yields the items on every page of a
+/// paginated function
+///
+/// It takes the same arguments as the function, starting from the page they
+/// select.
+fn iterator(
+    indent_level: usize,
+    method: &str,
+    path: &str,
+    function: &Function,
+    pagination: &Pagination,
+) -> String {
+    let name = name(method, path);
+    let names = argument_names(&function.arguments);
+
+    // Parsing guarantees that the query argument exists
+    let parameter = names
+        .iter()
+        .find(|(_, x)| {
+            x.location == Location::Query && x.name == pagination.parameter
+        })
+        .map_or_else(|| identifier(&pagination.parameter), |(x, _)| x.clone());
+
+    let arguments = forwarded_arguments(&function.arguments);
+
+    let fields = |xs: &[String]| {
+        let xs: Vec<_> = xs.iter().map(string_literal).collect();
+        format!("[{}]", xs.join(", "))
+    };
+
+    let mut code = format!(
+        "{i}async def iter_{name}(self, {}) -> AsyncIterator[{}]:\n",
+        self::arguments(&function.arguments),
+        type_to_string(&pagination.item, false),
+        i = indents(indent_level),
+    );
+
+    code.push_str(&docstring(
+        &format!(
+            "Iterate over the items on every page of `{name}`\n\nPages are \
+             fetched one at a time, starting from the one selected by \
+             `{parameter}`."
+        ),
+        &indents(indent_level + 1),
+    ));
+
+    // Other responses can have bodies too, but they aren't pages
+    let page = match return_type(&function.responses).1 {
+        Return::One => "_response.body",
+        Return::Many => "_response.body[1]",
+    };
+
+    // Argument names only start with an underscore when a digit follows, so
+    // these locals can't overwrite an argument like `page`
+    code.push_str(&format!(
+        "\n{i}while True:\n{j}_response = await \
+         self._{name}({arguments})\n\n{j}if _response.status // 100 != \
+         2:\n{k}raise \
+         aiohttp.ClientResponseError(_response.response.request_info, \
+         (_response.response,), status=_response.status)\n\n{j}_page = \
+         {page}\n{j}_items = _get_field(_page, {items})\n\n{j}if not \
+         _items:\n{k}return\n\n{j}for _item in _items:\n{k}yield \
+         _item\n\n{j}{parameter} = _get_field(_page, {next})\n\n{j}if \
+         {parameter} is None:\n{k}return",
+        items = fields(&pagination.items),
+        next = fields(&pagination.next),
+        i = indents(indent_level + 1),
+        j = indents(indent_level + 2),
+        k = indents(indent_level + 3),
+    ));
+
+    code
+}
+
 /// Generate the name of a function
-fn name<S1, S2>(method: S1, path: S2) -> String
+pub fn name<S1, S2>(method: S1, path: S2) -> String
 where
     S1: AsRef<str>,
     S2: AsRef<str>,
@@ -324,51 +621,127 @@ where
     format!("{}_{}", method.as_ref(), path.as_ref()).to_snake_case()
 }
 
+/// Generate the name of the model of a function's response headers
+///
+/// For example, `GetFooFooIdHeaders`.
+pub fn header_model<S1, S2>(method: S1, path: S2) -> String
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    format!("{}Headers", name(method, path).to_upper_camel_case())
+}
+
+/// Names that arguments can't have, since generated functions already use them
+/// for something else
+const RESERVED: &[&str] = &["params", "headers", "resp", "request_options"];
+
+/// Choose the Python name of each argument of a function
+///
+/// Names are unique within the function. An argument whose name is already
+/// taken gets its location added, like `id_query` next to a path argument
+/// `id`, and then a number if that's taken too. The body is named first, so
+/// it's always `body`. The names are in the same order as `arguments`.
+fn argument_names(arguments: &[Argument]) -> Vec<(String, &Argument)> {
+    let mut taken: Vec<String> =
+        RESERVED.iter().map(|x| (*x).to_owned()).collect();
+    let mut names = vec![String::new(); arguments.len()];
+
+    let body_first = arguments
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.location == Location::Body)
+        .chain(
+            arguments
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| x.location != Location::Body),
+        );
+
+    for (index, argument) in body_first {
+        let mut name = identifier(&argument.name);
+
+        if taken.contains(&name) {
+            let location = match argument.location {
+                Location::Query => "query",
+                Location::Path => "path",
+                Location::Header => "header",
+                Location::Body => "body",
+            };
+
+            name = format!("{name}_{location}");
+
+            let base = name.clone();
+            let mut suffix = 2;
+
+            while taken.contains(&name) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+        }
+
+        taken.push(name.clone());
+        names[index] = name;
+    }
+
+    names.into_iter().zip(arguments).collect()
+}
+
 /// Generate the arguments that a function takes
 ///
 /// These go between the `(` and `)`. Return value will not contain any
 /// newlines.
 fn arguments(arguments: &[Argument]) -> String {
     let mut args = String::new();
+    let names = argument_names(arguments);
 
     // Body argument goes first
-    arguments.iter().filter(|x| x.location == Location::Body).for_each(|x| {
-        args.push_str(&format!(
-            "{}: {}, ",
-            &x.name,
-            type_to_string(&x.r#type, false)
-        ));
-    });
+    names.iter().filter(|(_, x)| x.location == Location::Body).for_each(
+        |(name, x)| {
+            args.push_str(&format!(
+                "{name}: {}, ",
+                type_to_string(&x.r#type, false)
+            ));
+        },
+    );
 
     // Other non-optional arguments go next
-    arguments
+    names
         .iter()
-        .filter(|x| {
-            x.location != Location::Body
-                && (!matches!(x.r#type, Type::Option(_)))
-        })
-        .for_each(|x| {
+        .filter(|(_, x)| x.location != Location::Body && x.required)
+        .for_each(|(name, x)| {
             args.push_str(&format!(
-                "{}: {}, ",
-                &x.name,
+                "{name}: {}, ",
                 type_to_string(&x.r#type, false)
             ));
         });
 
-    // Optional arguments go last, defaulted to `None`
-    arguments
+    // Optional arguments go last, defaulted to what the server would use or
+    // `None`. Mutable defaults would be shared between calls, so those are
+    // left to the server instead, as are the defaults of deprecated arguments
+    // so that passing them can be detected.
+    names
         .iter()
-        .filter(|x| {
-            x.location != Location::Body && matches!(x.r#type, Type::Option(_))
-        })
-        .for_each(|x| {
-            args.push_str(&format!(
-                "{}: {} = None, ",
-                &x.name,
-                type_to_string(&x.r#type, false)
-            ));
+        .filter(|(_, x)| x.location != Location::Body && !x.required)
+        .for_each(|(name, x)| match &x.default {
+            Some(default) if !is_mutable(default) && !x.deprecated => {
+                args.push_str(&format!(
+                    "{name}: {} = {}, ",
+                    type_to_string(&x.r#type, false),
+                    literal(default)
+                ));
+            }
+            _ => {
+                args.push_str(&format!(
+                    "{name}: {} = None, ",
+                    type_to_string(&x.r#type.clone().optional(), false)
+                ));
+            }
         });
 
+    // Settings like timeouts can be overridden for each request
+    args.push_str("*, request_options: Optional[RequestOptions] = None, ");
+
     args
 }
 
@@ -399,7 +772,7 @@ fn return_type(responses: &BTreeMap<String, Type>) -> (String, Return) {
                 .into_iter()
                 .map(|(code, ty)| (code, type_to_string(&ty, false)))
                 .fold(String::from("Union["), |mut acc, (code, ty)| {
-                    acc.push_str(&format!("Tuple[Literal[{code}], {ty}], ",));
+                    acc.push_str(&format!("Tuple[Literal[{code}], {ty}], "));
 
                     acc
                 });
@@ -0,0 +1,160 @@
+//! Ordering models by the models they refer to
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which models each model refers to
+pub type Graph<'a> = BTreeMap<&'a str, BTreeSet<&'a str>>;
+
+/// Group models that refer to each other, directly or indirectly
+///
+/// Every model is in exactly one group, and each group comes after every group
+/// it refers to, so generating the groups in order only needs forward
+/// references within a group. Models are sorted within their group. This is
+/// [Tarjan's algorithm][tarjan].
+///
+/// [tarjan]: https://en.wikipedia.org/wiki/Tarjan%27s_strongly_connected_components_algorithm
+pub fn strongly_connected_components<'a>(
+    graph: &Graph<'a>,
+) -> Vec<Vec<&'a str>> {
+    let mut tarjan = Tarjan {
+        graph,
+        next_index: 0,
+        indices: BTreeMap::new(),
+        low_links: BTreeMap::new(),
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+
+    for node in graph.keys() {
+        if !tarjan.indices.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan.components
+}
+
+/// Whether a group of models refers to itself
+///
+/// This is true for every group with more than one model, and for single
+/// models that refer to themselves.
+pub fn is_cyclic(graph: &Graph<'_>, component: &[&str]) -> bool {
+    match component {
+        [node] => graph.get(node).is_some_and(|x| x.contains(node)),
+        _ => true,
+    }
+}
+
+/// State for finding strongly connected components
+struct Tarjan<'a, 'g> {
+    /// The graph being searched
+    graph: &'g Graph<'a>,
+
+    /// The index given to the next node visited
+    next_index: usize,
+
+    /// The order nodes were first visited in
+    indices: BTreeMap<&'a str, usize>,
+
+    /// The smallest index reachable from each node that's still on the stack
+    low_links: BTreeMap<&'a str, usize>,
+
+    /// Nodes visited but not yet assigned to a component
+    stack: Vec<&'a str>,
+
+    /// The components found so far
+    components: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a, '_> {
+    /// Visit a node and everything reachable from it
+    fn visit(&mut self, node: &'a str) {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        self.indices.insert(node, index);
+        self.low_links.insert(node, index);
+        self.stack.push(node);
+
+        for &next in self.graph.get(node).into_iter().flatten() {
+            let low_link = if !self.indices.contains_key(next) {
+                self.visit(next);
+                self.low_links.get(next).copied()
+            } else if self.stack.contains(&next) {
+                self.indices.get(next).copied()
+            } else {
+                None
+            };
+
+            if let Some(low_link) = low_link {
+                let current = self.low_links.entry(node).or_insert(index);
+                *current = (*current).min(low_link);
+            }
+        }
+
+        // This is the first node visited in its component, so the rest of
+        // the component is above it on the stack
+        if self.low_links.get(node) == Some(&index) {
+            let start =
+                self.stack.iter().rposition(|x| *x == node).unwrap_or_default();
+
+            let mut component = self.stack.split_off(start);
+            component.sort_unstable();
+
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_cyclic, strongly_connected_components, Graph};
+
+    /// Build a graph from each node and the nodes it refers to
+    fn graph<'a>(edges: &[(&'a str, &[&'a str])]) -> Graph<'a> {
+        edges
+            .iter()
+            .map(|(node, next)| (*node, next.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Models come after the models they refer to
+    #[test]
+    fn orders_dependencies_first() {
+        let graph = graph(&[("A", &["B"]), ("B", &["C"]), ("C", &[])]);
+
+        assert_eq!(
+            strongly_connected_components(&graph),
+            [vec!["C"], vec!["B"], vec!["A"]]
+        );
+    }
+
+    /// Models that refer to each other share a sorted group, which comes
+    /// before the models referring to it
+    #[test]
+    fn groups_cycles() {
+        let graph = graph(&[
+            ("A", &["D"]),
+            ("B", &["C"]),
+            ("C", &["D"]),
+            ("D", &["B", "E"]),
+            ("E", &[]),
+        ]);
+
+        assert_eq!(
+            strongly_connected_components(&graph),
+            [vec!["E"], vec!["B", "C", "D"], vec!["A"]]
+        );
+    }
+
+    /// Only groups that refer to themselves are cyclic
+    #[test]
+    fn detects_cycles() {
+        let graph =
+            graph(&[("A", &["A"]), ("B", &["C"]), ("C", &["B"]), ("D", &[])]);
+
+        assert!(is_cyclic(&graph, &["A"]));
+        assert!(is_cyclic(&graph, &["B", "C"]));
+        assert!(!is_cyclic(&graph, &["D"]));
+    }
+}
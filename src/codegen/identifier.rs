@@ -0,0 +1,42 @@
+//! Convert names from OpenAPI into valid Python identifiers
+
+use heck::ToSnakeCase;
+
+/// Names that can't be used as Python identifiers in generated code
+///
+/// This is Python's hard keywords plus `self`, since every generated function
+/// is a method.
+const RESERVED: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break",
+    "class", "continue", "def", "del", "elif", "else", "except", "finally",
+    "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "self", "try", "while", "with",
+    "yield",
+];
+
+/// Convert an arbitrary name into a valid `snake_case` Python identifier
+///
+/// For example, `call-id` becomes `call_id` and `from` becomes `from_`.
+pub fn identifier<S: AsRef<str>>(name: S) -> String {
+    let mut ident = name.as_ref().to_snake_case();
+
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if RESERVED.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// Whether a name can be used as a Python identifier as-is
+pub fn is_identifier<S: AsRef<str>>(name: S) -> bool {
+    let name = name.as_ref();
+
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED.contains(&name)
+}
@@ -1,8 +1,28 @@
-from typing import Optional, List, Set, Dict, Any, Union, Tuple, Literal
+from datetime import datetime, timezone
+from email.utils import parsedate_to_datetime
+from types import TracebackType
+from typing import (
+    Optional,
+    List,
+    Set,
+    Dict,
+    Any,
+    Union,
+    Tuple,
+    Literal,
+    AsyncIterator,
+    Awaitable,
+    Callable,
+    TypedDict,
+    Generic,
+    Mapping,
+    Type,
+    TypeVar,
+    get_origin,
+)
+from urllib.parse import quote
+import asyncio
+import warnings
 
 import aiohttp
 from pydantic import BaseModel, Field, parse_obj_as
-
-class _BaseModel(BaseModel):
-    class Config:
-        allow_population_by_field_name = True
@@ -0,0 +1,169 @@
+//! Convert values from the document into Python literals
+//!
+//! Everything user-supplied that ends up inside a string or docstring in the
+//! generated code must go through here, so that quotes and backslashes in it
+//! can't change the meaning of the code.
+
+use serde_json::Value;
+
+/// Quote a string as a Python string literal
+pub fn string_literal<S: AsRef<str>>(s: S) -> String {
+    let mut literal = String::from("\"");
+
+    for c in s.as_ref().chars() {
+        match c {
+            '\\' => literal.push_str(r"\\"),
+            '"' => literal.push_str("\\\""),
+            '\n' => literal.push_str(r"\n"),
+            '\r' => literal.push_str(r"\r"),
+            '\t' => literal.push_str(r"\t"),
+            c if c.is_control() => {
+                literal.push_str(&format!(r"\u{:04x}", u32::from(c)));
+            }
+            c => literal.push(c),
+        }
+    }
+
+    literal.push('"');
+
+    literal
+}
+
+/// Generate a docstring, indented with `indent`, without a trailing newline
+///
+/// The text is re-indented to line up with the quotes, and escaped so that it
+/// reads the same at runtime as it does in the document.
+pub fn docstring(text: &str, indent: &str) -> String {
+    let mut code = format!("{indent}\"\"\"\n");
+
+    // Normalize line endings so stray carriage returns can't end up in the
+    // generated code
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    for line in text.trim().lines() {
+        if !line.trim().is_empty() {
+            code.push_str(indent);
+            code.push_str(&escape_docstring(line.trim_end()));
+        }
+
+        code.push('\n');
+    }
+
+    code.push_str(indent);
+    code.push_str("\"\"\"");
+
+    code
+}
+
+/// Escape a line of text for use inside a triple-quoted string
+///
+/// Quotes are only escaped where they could end the string, to keep the
+/// generated code readable.
+fn escape_docstring(line: &str) -> String {
+    let mut escaped = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '"' if matches!(chars.peek(), Some('"') | None) => {
+                escaped.push_str("\\\"");
+            }
+            '\t' => escaped.push('\t'),
+            c if c.is_control() => {
+                escaped.push_str(&format!(r"\u{:04x}", u32::from(c)));
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Convert a JSON value into a Python expression that evaluates to it
+pub fn literal(value: &Value) -> String {
+    match value {
+        Value::Null => "None".to_owned(),
+        Value::Bool(true) => "True".to_owned(),
+        Value::Bool(false) => "False".to_owned(),
+
+        // JSON numbers are already valid Python literals
+        Value::Number(_) => value.to_string(),
+        Value::String(x) => string_literal(x),
+        Value::Array(xs) => {
+            let items: Vec<_> = xs.iter().map(literal).collect();
+
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(xs) => {
+            let items: Vec<_> = xs
+                .iter()
+                .map(|(k, v)| format!("{}: {}", string_literal(k), literal(v)))
+                .collect();
+
+            format!("{{{}}}", items.join(", "))
+        }
+    }
+}
+
+/// Whether the Python value for a JSON value could be modified in place
+///
+/// Such values can't be used as defaults directly, since every use would share
+/// the same object.
+pub fn is_mutable(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{docstring, literal, string_literal};
+
+    /// Quotes, backslashes, and control characters are escaped
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string_literal(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(string_literal("a\nb\r\tc\u{7}"), r#""a\nb\r\tc\u0007""#);
+    }
+
+    /// Characters outside ASCII are kept as they are
+    #[test]
+    fn keeps_non_ascii() {
+        assert_eq!(string_literal("é ✓ 😀"), "\"é ✓ 😀\"");
+        assert_eq!(docstring("é ✓ 😀", ""), "\"\"\"\né ✓ 😀\n\"\"\"");
+    }
+
+    /// Quotes that could end a docstring are escaped, and backslashes always
+    /// are
+    #[test]
+    fn escapes_docstrings() {
+        assert_eq!(
+            docstring(r#"Say """ or "hi" \n"#, "    "),
+            "    \"\"\"\n    Say \\\"\\\"\" or \"hi\" \\\\n\n    \"\"\""
+        );
+        assert_eq!(
+            docstring(r#"ends with ""#, ""),
+            "\"\"\"\nends with \\\"\n\"\"\""
+        );
+    }
+
+    /// Docstrings are re-indented line by line, without trailing whitespace
+    /// or blank lines around them
+    #[test]
+    fn indents_docstrings() {
+        assert_eq!(
+            docstring("\n  first  \r\n\n  second\rthird\n", "  "),
+            "  \"\"\"\n  first\n\n    second\n  third\n  \"\"\""
+        );
+    }
+
+    /// JSON values become the Python values they stand for
+    #[test]
+    fn converts_values() {
+        assert_eq!(
+            literal(&json!({"a": [1, 2.5, null, true, false, "\"x\""]})),
+            r#"{"a": [1, 2.5, None, True, False, "\"x\""]}"#
+        );
+    }
+}
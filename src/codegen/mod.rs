@@ -4,10 +4,33 @@
 const INDENT: &str = "    ";
 
 mod functions;
-pub use functions::functions;
+pub use functions::{functions, header_model};
+
+mod graph;
+
+mod identifier;
+pub use identifier::{identifier, is_identifier};
+
+mod literal;
+pub use literal::{docstring, is_mutable, literal, string_literal};
 
 mod module;
 pub use module::module;
 
+mod options;
+pub use options::Options;
+
+mod package;
+pub use package::package;
+
+mod pagination;
+pub use pagination::paginate;
+
+mod prepare;
+pub use prepare::{prepare, Prepared};
+
 mod types;
-pub use types::{type_to_string, types};
+pub use types::{
+    drop_skipped_models, header_models, model_names, request_models,
+    tree_shake, type_to_string, types, use_request_models,
+};
@@ -4,41 +4,81 @@ use std::collections::BTreeMap;
 
 use okapi::openapi3::OpenApi;
 
+use super::{Options, Prepared};
+use crate::parse::{Diagnostics, SecurityScheme};
+
 /// Generate the API client module
-pub fn module(openapi: &OpenApi) -> String {
+pub fn module(
+    openapi: &OpenApi,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    let Prepared {
+        openapi,
+        functions: fs,
+        security_schemes,
+        request_models,
+    } = super::prepare(openapi, options, diagnostics);
+    let openapi = &openapi;
+
     let mut module = String::new();
 
-    let module_docs = {
-        let mut module_docs = format!("{} HTTP API client", openapi.info.title);
+    module.push_str(&docs(openapi));
+    module.push_str("\n\n");
 
-        if let Some(description) = &openapi.info.description {
-            module_docs.push_str("\n\n");
-            module_docs.push_str(description);
-        };
+    module.push_str(include_str!("imports.py"));
+    module.push('\n');
 
-        format!(r#""""{module_docs}""""#)
-    };
+    module.push_str(include_str!("base_model.py"));
+    module.push_str("\n\n");
 
-    // TODO: remove this when more auth methods are supported
-    #[allow(clippy::zero_sized_map_values)]
-    let security_schemes = {
-        openapi.components.as_ref().map_or_else(BTreeMap::default, |x| {
-            crate::parse::security_schemes(&x.security_schemes)
-        })
-    };
+    module.push_str(include_str!("params.py"));
+    module.push_str("\n\n");
 
-    module.push_str(&module_docs);
+    module.push_str(include_str!("pagination.py"));
     module.push_str("\n\n");
 
-    module.push_str(include_str!("imports.py"));
+    module.push_str(include_str!("response.py"));
     module.push_str("\n\n");
 
-    module.push_str(&crate::codegen::types(openapi));
+    module.push_str(&crate::codegen::types(
+        openapi,
+        &request_models,
+        diagnostics,
+    ));
+    module.push_str(&crate::codegen::header_models(&fs));
+
+    module.push_str(include_str!("request_options.py"));
+    module.push_str("\n\n");
 
     module.push_str(include_str!("api_client.py"));
     module.push_str("\n\n");
 
-    module.push_str(&crate::codegen::functions(openapi, &security_schemes));
+    module.push_str(&crate::codegen::functions(&fs, &security_schemes));
 
     module
 }
+
+/// Generate the module-level documentation for the API client
+pub fn docs(openapi: &OpenApi) -> String {
+    let mut module_docs = format!("{} HTTP API client", openapi.info.title);
+
+    if let Some(description) = &openapi.info.description {
+        module_docs.push_str("\n\n");
+        module_docs.push_str(description);
+    }
+
+    super::docstring(&module_docs, "")
+}
+
+/// Parse the security schemes the API client can authenticate with
+// TODO: remove this when more auth methods are supported
+#[allow(clippy::zero_sized_map_values)]
+pub fn security_schemes(
+    openapi: &OpenApi,
+    diagnostics: &mut Diagnostics,
+) -> BTreeMap<String, SecurityScheme> {
+    openapi.components.as_ref().map_or_else(BTreeMap::default, |x| {
+        crate::parse::security_schemes(&x.security_schemes, diagnostics)
+    })
+}
@@ -0,0 +1,14 @@
+//! Settings for how code is generated
+
+use crate::parse::Pagination;
+
+/// Settings for how code is generated
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Only generate models for the schemas that the generated operations
+    /// use, directly or through other schemas
+    pub tree_shake: bool,
+
+    /// How to page through operations without an `x-pagination` extension
+    pub pagination: Option<Pagination>,
+}
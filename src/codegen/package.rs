@@ -0,0 +1,481 @@
+//! Generate an installable Python package for the API client
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use heck::ToUpperCamelCase;
+use okapi::openapi3::OpenApi;
+
+use super::{identifier, Options, Prepared};
+use crate::parse::{
+    Diagnostics, Error, ErrorKind, Function, Functions, SecurityScheme,
+};
+
+/// Generate the files of a Python package containing the API client
+///
+/// The keys of the returned map are paths relative to the package's project
+/// directory, and the values are the contents of those files.
+pub fn package(
+    openapi: &OpenApi,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> BTreeMap<PathBuf, String> {
+    let name = identifier(&openapi.info.title);
+    let root = PathBuf::from(&name);
+
+    let Prepared {
+        openapi,
+        functions: fs,
+        security_schemes,
+        request_models,
+    } = super::prepare(openapi, options, diagnostics);
+    let openapi = &openapi;
+
+    let mut files = BTreeMap::new();
+
+    files.insert(
+        PathBuf::from("pyproject.toml"),
+        pyproject(openapi, &name, diagnostics),
+    );
+
+    files.insert(root.join("py.typed"), String::new());
+
+    files.insert(
+        root.join("__init__.py"),
+        format!(
+            "{}\n\nfrom ._request_options import AfterResponseHook, \
+             BeforeRequestHook, RequestOptions\nfrom ._response import \
+             RawResponse\nfrom .client import ApiClient\nfrom .models import \
+             *\n",
+            super::module::docs(openapi),
+        ),
+    );
+
+    files.insert(
+        root.join("models.py"),
+        models(openapi, &request_models, &fs, diagnostics),
+    );
+
+    // Code shared by the modules containing client methods
+    for (file, docs, code) in [
+        (
+            "_params.py",
+            "Serialization of path, query, and header parameters",
+            include_str!("params.py"),
+        ),
+        (
+            "_request_options.py",
+            "Settings for individual requests",
+            include_str!("request_options.py"),
+        ),
+        (
+            "_pagination.py",
+            "Following fields through paginated responses",
+            include_str!("pagination.py"),
+        ),
+        (
+            "_response.py",
+            "Responses along with their status and headers",
+            include_str!("response.py"),
+        ),
+    ] {
+        files.insert(root.join(file), helper_module(docs, code));
+    }
+
+    // Functions are grouped by their first tag, untagged ones go directly on
+    // the client
+    let mut groups: BTreeMap<Option<&str>, Vec<_>> = BTreeMap::new();
+
+    for (key, function) in &fs {
+        groups
+            .entry(function.tags.first().map(String::as_str))
+            .or_default()
+            .push((key, function));
+    }
+
+    let names = tag_names(groups.keys().copied().flatten());
+
+    let mut mixins = Vec::new();
+    let mut client = super::module::docs(openapi);
+    client.push_str("\n\n");
+    client.push_str(&module_header());
+
+    for (tag, fs) in &groups {
+        let Some(tag) = tag else {
+            continue;
+        };
+
+        let (module, class) = names[tag].clone();
+
+        let code = tag_module(openapi, tag, &class, fs, &security_schemes);
+        files.insert(root.join(format!("{module}.py")), code);
+
+        client.push_str(&format!("from .{module} import {class}\n"));
+        mixins.push(class);
+    }
+
+    client.push_str("\n\n");
+
+    client.push_str(&api_client(&mixins));
+
+    client.push_str("\n\n");
+
+    if let Some(fs) = groups.get(&None) {
+        client.push_str(&crate::codegen::functions(
+            fs.iter().copied(),
+            &security_schemes,
+        ));
+    }
+
+    files.insert(root.join("client.py"), client);
+
+    files
+}
+
+/// Pick the module and class names of each tag's mixin
+///
+/// Both come from the tag's identifier, so tags that differ only in
+/// punctuation or case, such as `call-control` and `call_control`, would get
+/// the same names. Those get a number after their name instead.
+fn tag_names<'a, I>(tags: I) -> BTreeMap<&'a str, (String, String)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut taken = BTreeSet::new();
+    let mut names = BTreeMap::new();
+
+    for tag in tags {
+        let stem = identifier(tag);
+
+        for n in 1.. {
+            let stem = match n {
+                1 => stem.clone(),
+                _ => format!("{stem}_{n}"),
+            };
+
+            let module = format!("{stem}_api");
+            let mut class = format!("{}Api", stem.to_upper_camel_case());
+
+            // Tags can start with a digit, like `2fa`
+            if class.starts_with(|c: char| c.is_ascii_digit()) {
+                class.insert(0, '_');
+            }
+
+            if !taken.contains(&module) && !taken.contains(&class) {
+                taken.insert(module.clone());
+                taken.insert(class.clone());
+                names.insert(tag, (module, class));
+                break;
+            }
+        }
+    }
+
+    names
+}
+
+/// Generate the module containing the mixin class for a tag's methods
+// TODO: remove this when more auth methods are supported
+#[allow(clippy::zero_sized_map_values)]
+fn tag_module(
+    openapi: &OpenApi,
+    tag: &str,
+    class: &str,
+    fs: &[(&(String, String), &Function)],
+    security_schemes: &BTreeMap<String, SecurityScheme>,
+) -> String {
+    let mut code = super::docstring(&format!("{tag} operations"), "");
+    code.push_str("\n\n");
+    code.push_str(&module_header());
+    code.push_str(&format!("class {class}:\n"));
+
+    if let Some(description) = openapi
+        .tags
+        .iter()
+        .find(|x| x.name == tag)
+        .and_then(|x| x.description.as_ref())
+    {
+        code.push_str(&super::docstring(description, super::INDENT));
+        code.push_str("\n\n");
+    }
+
+    // These are provided by `ApiClient`, which inherits from this class
+    code.push_str(&format!(
+        "{i}_base_url: str\n{i}_auth: aiohttp.BasicAuth\n{i}_request: \
+         Callable[..., Awaitable[aiohttp.ClientResponse]]\n\n",
+        i = super::INDENT
+    ));
+
+    code.push_str(&crate::codegen::functions(
+        fs.iter().copied(),
+        security_schemes,
+    ));
+
+    code
+}
+
+/// Generate the module containing every model
+fn models(
+    openapi: &OpenApi,
+    request_models: &BTreeMap<String, String>,
+    fs: &Functions,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    let mut models = super::docstring(
+        &format!("{} HTTP API models", openapi.info.title),
+        "",
+    );
+    models.push_str("\n\n");
+    models.push_str(include_str!("imports.py"));
+    models.push('\n');
+
+    // The other modules import every model with `*`, which would skip the
+    // ones starting with an underscore without this
+    models.push_str("__all__ = [\n");
+    for name in crate::codegen::model_names(openapi, request_models, fs) {
+        models.push_str(&format!(
+            "{}{},\n",
+            super::INDENT,
+            super::string_literal(&name)
+        ));
+    }
+    models.push_str("]\n\n\n");
+
+    models.push_str(include_str!("base_model.py"));
+    models.push_str("\n\n");
+    models.push_str(&crate::codegen::types(
+        openapi,
+        request_models,
+        diagnostics,
+    ));
+    models.push_str(&crate::codegen::header_models(fs));
+
+    models
+}
+
+/// Generate the `ApiClient` class, inheriting the methods of each tag's class
+fn api_client(mixins: &[String]) -> String {
+    if mixins.is_empty() {
+        include_str!("api_client.py").to_owned()
+    } else {
+        include_str!("api_client.py").replacen(
+            "class ApiClient:",
+            &format!("class ApiClient({}):", mixins.join(", ")),
+            1,
+        )
+    }
+}
+
+/// Generate a module of code shared by the rest of the package
+fn helper_module(docs: &str, code: &str) -> String {
+    let mut module = super::docstring(docs, "");
+    module.push_str("\n\n");
+    module.push_str(include_str!("imports.py"));
+    module.push_str("\n\n");
+    module.push_str(code);
+
+    module
+}
+
+/// Imports needed by every module containing client methods
+fn module_header() -> String {
+    let mut code = String::from(include_str!("imports.py"));
+
+    code.push_str("\nfrom ._pagination import _get_field\n");
+    code.push_str(
+        "from ._params import _serialize_header_param, _serialize_path_param, \
+         _serialize_query_param\n",
+    );
+    code.push_str(
+        "from ._request_options import AfterResponseHook, BeforeRequestHook, \
+         RequestOptions\n",
+    );
+    code.push_str("from ._response import RawResponse, _parse_headers\n");
+    code.push_str("from .models import *\n\n\n");
+
+    code
+}
+
+/// Quote a string for TOML
+///
+/// JSON strings are also valid TOML basic strings.
+fn toml_string<S: AsRef<str>>(s: S) -> String {
+    serde_json::Value::from(s.as_ref()).to_string()
+}
+
+/// Make a project name that's valid under PEP 508 from a package name
+///
+/// Project names may only contain ASCII letters, digits, `-`, `_`, and `.`,
+/// and have to start and end with a letter or digit.
+fn project_name(name: &str) -> String {
+    let name = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if name.is_empty() {
+        "api-client".to_owned()
+    } else {
+        name
+    }
+}
+
+/// Make a version that's valid under PEP 440 from the document's version
+///
+/// Versions like `v1` and `2024-01-01` are common in documents, and become
+/// `1` and `2024.1.1`. Anything else that isn't already valid becomes `0.0.0`,
+/// which is recorded in `diagnostics`.
+fn project_version(version: &str, diagnostics: &mut Diagnostics) -> String {
+    let version = version.trim();
+    let stripped = version.trim_start_matches(['v', 'V']);
+
+    if is_pep440(stripped) {
+        return stripped.to_owned();
+    }
+
+    let numbers: Option<Vec<String>> = stripped
+        .split(['.', '-', '_'])
+        .map(|x| x.parse::<u64>().ok().map(|x| x.to_string()))
+        .collect();
+
+    if let Some(numbers) = numbers {
+        return numbers.join(".");
+    }
+
+    diagnostics.warn(
+        Error::from(ErrorKind::InvalidVersion(version.to_owned()))
+            .within(["info", "version"]),
+    );
+
+    "0.0.0".to_owned()
+}
+
+/// Whether a version is valid under PEP 440, in its normalized form
+///
+/// That's a release like `1.2`, then optionally a pre-release like `rc1`, a
+/// post-release like `.post1`, a development release like `.dev1`, and a
+/// local version like `+ubuntu.1`, in that order.
+fn is_pep440(version: &str) -> bool {
+    /// Strip a number off the start of `x`
+    fn number(x: &str) -> Option<&str> {
+        let rest = x.trim_start_matches(|c: char| c.is_ascii_digit());
+
+        (rest.len() < x.len()).then_some(rest)
+    }
+
+    let (public, local) = match version.split_once('+') {
+        Some((public, local)) => (public, Some(local)),
+        None => (version, None),
+    };
+
+    let Some(mut rest) = number(public) else {
+        return false;
+    };
+
+    while let Some(x) = rest.strip_prefix('.').and_then(number) {
+        rest = x;
+    }
+
+    if let Some(x) = ["a", "b", "rc"].iter().find_map(|x| rest.strip_prefix(x))
+    {
+        let Some(x) = number(x) else {
+            return false;
+        };
+
+        rest = x;
+    }
+
+    for prefix in [".post", ".dev"] {
+        if let Some(x) = rest.strip_prefix(prefix) {
+            let Some(x) = number(x) else {
+                return false;
+            };
+
+            rest = x;
+        }
+    }
+
+    rest.is_empty()
+        && local.is_none_or(|x| {
+            x.split('.').all(|x| {
+                !x.is_empty() && x.bytes().all(|x| x.is_ascii_alphanumeric())
+            })
+        })
+}
+
+/// Generate the packaging metadata for the package
+fn pyproject(
+    openapi: &OpenApi,
+    name: &str,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    // Keep these in sync with `requirements.in`
+    let dependencies = include_str!("../../requirements.in")
+        .lines()
+        .filter(|x| !x.trim().is_empty())
+        .fold(String::new(), |mut acc, x| {
+            acc.push_str(&format!("    {},\n", toml_string(x)));
+            acc
+        });
+
+    format!(
+        "[build-system]\nrequires = [\"setuptools>=61\"]\nbuild-backend = \
+         \"setuptools.build_meta\"\n\n[project]\nname = {}\nversion = \
+         {}\ndescription = {}\nrequires-python = \">=3.8\"\ndependencies = \
+         [\n{dependencies}]\n\n[tool.setuptools.package-data]\n{} = \
+         [\"py.typed\"]\n",
+        toml_string(project_name(name)),
+        toml_string(project_version(&openapi.info.version, diagnostics)),
+        toml_string(format!("{} HTTP API client", openapi.info.title)),
+        toml_string(name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{project_name, project_version};
+    use crate::parse::Diagnostics;
+
+    /// Normalize a version, along with whether that was reported
+    fn version(x: &str) -> (String, bool) {
+        let mut diagnostics = Diagnostics::default();
+        let version = project_version(x, &mut diagnostics);
+        let reported = diagnostics.disallowed(&[]).next().is_some();
+
+        (version, reported)
+    }
+
+    /// Valid versions are kept as they are
+    #[test]
+    fn keeps_valid_versions() {
+        for x in ["1", "1.2.3", "1.0rc1", "2.0b3.post1.dev2", "1.0+local.7"] {
+            assert_eq!(version(x), (x.to_owned(), false));
+        }
+    }
+
+    /// Common versions that aren't valid are fixed up
+    #[test]
+    fn normalizes_versions() {
+        assert_eq!(version("v1"), ("1".to_owned(), false));
+        assert_eq!(version("V2.1"), ("2.1".to_owned(), false));
+        assert_eq!(version("2024-01-01"), ("2024.1.1".to_owned(), false));
+    }
+
+    /// Anything else is reported and replaced
+    #[test]
+    fn replaces_invalid_versions() {
+        for x in ["", "latest", "1.0-beta", "1..2", "1.0+", "1.0rc"] {
+            assert_eq!(version(x), ("0.0.0".to_owned(), true), "{x}");
+        }
+    }
+
+    /// Project names only keep ASCII letters and digits, joined by `-`
+    #[test]
+    fn project_names() {
+        assert_eq!(project_name("_2fa_service"), "2fa-service");
+        assert_eq!(project_name("my_api"), "my-api");
+        assert_eq!(project_name("_"), "api-client");
+    }
+}
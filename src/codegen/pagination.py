@@ -0,0 +1,23 @@
+def _get_field(value: Any, path: List[str]) -> Any:
+    """
+    Follow fields through a response by their names in the API
+
+    Returns `None` if any of the fields is missing.
+    """
+
+    for name in path:
+        if isinstance(value, BaseModel):
+            value = next(
+                (
+                    getattr(value, key)
+                    for key, field in value.__fields__.items()
+                    if field.alias == name
+                ),
+                None,
+            )
+        elif isinstance(value, dict):
+            value = value.get(name)
+        else:
+            return None
+
+    return value
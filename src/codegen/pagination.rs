@@ -0,0 +1,101 @@
+//! Work out how to page through the results of functions
+
+use okapi::{
+    openapi3::{OpenApi, SchemaObject},
+    schemars::Map,
+};
+
+use crate::parse::{Function, Location, Pagination, Type};
+
+/// Fill in the pagination of functions
+///
+/// Functions without an `x-pagination` extension are paginated by `pattern`
+/// if they take its query parameter and their successful response has both
+/// of its fields, the one with the items being a list. The type of the items
+/// is looked up for every paginated function.
+pub fn paginate<'a, I>(openapi: &OpenApi, fs: I, pattern: Option<&Pagination>)
+where
+    I: IntoIterator<Item = &'a mut Function>,
+{
+    let empty = Map::new();
+    let schemas = openapi.components.as_ref().map_or(&empty, |x| &x.schemas);
+
+    for function in fs {
+        // Pages come from the only successful response
+        let Some((_, response)) = function.success_response() else {
+            function.pagination = None;
+            continue;
+        };
+
+        let response = response.clone();
+
+        if let Some(pagination) = &mut function.pagination {
+            pagination.item = field(schemas, &response, &pagination.items)
+                .and_then(|x| item(&x))
+                .unwrap_or(Type::Any);
+
+            continue;
+        }
+
+        let Some(pattern) = pattern else {
+            continue;
+        };
+
+        let has_parameter = function.arguments.iter().any(|x| {
+            x.location == Location::Query && x.name == pattern.parameter
+        });
+
+        let item =
+            field(schemas, &response, &pattern.items).and_then(|x| item(&x));
+        let has_next = field(schemas, &response, &pattern.next).is_some();
+
+        if let (true, true, Some(item)) = (has_parameter, has_next, item) {
+            function.pagination = Some(Pagination {
+                item,
+                ..pattern.clone()
+            });
+        }
+    }
+}
+
+/// Find the type of the field at the end of `path`, starting from a value of
+/// type `r#type`
+///
+/// Objects without a model of their own are plain dictionaries at runtime,
+/// so the fields within them are [`Type::Any`](Type::Any).
+fn field(
+    schemas: &Map<String, SchemaObject>,
+    r#type: &Type,
+    path: &[String],
+) -> Option<Type> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(r#type.clone());
+    };
+
+    match r#type {
+        Type::Option(x) => field(schemas, x, path),
+        Type::Ref(x) => {
+            let name = x.rsplit('/').next()?;
+
+            match Type::try_from(schemas.get(name)?).ok()? {
+                Type::Struct(s) => {
+                    field(schemas, &s.fields.get(first)?.r#type, rest)
+                }
+                other => field(schemas, &other, path),
+            }
+        }
+        Type::Struct(s) => field(schemas, &s.fields.get(first)?.r#type, rest)
+            .map(|_| Type::Any),
+        _ => None,
+    }
+}
+
+/// Find the type of the items of a list
+fn item(r#type: &Type) -> Option<Type> {
+    match r#type {
+        Type::Option(x) => item(x),
+        Type::List(x) | Type::Set(x) => Some((**x).clone()),
+        Type::Any => Some(Type::Any),
+        _ => None,
+    }
+}
@@ -0,0 +1,152 @@
+def _param_to_str(value: Any) -> str:
+    """
+    Convert a single parameter value into its URL representation
+    """
+
+    if isinstance(value, bool):
+        return "true" if value else "false"
+
+    return str(value)
+
+
+def _param_to_collection(value: Any) -> Any:
+    """
+    Convert models into dicts and sets into lists so they can be serialized
+    """
+
+    if isinstance(value, BaseModel):
+        return value.dict(by_alias=True, exclude_none=True)
+
+    if isinstance(value, (set, tuple)):
+        return list(value)
+
+    return value
+
+
+def _serialize_query_param(
+    name: str,
+    value: Any,
+    style: str,
+    explode: bool,
+) -> List[Tuple[str, str]]:
+    """
+    Serialize a query parameter according to its OpenAPI `style` and `explode`
+    """
+
+    if value is None:
+        return []
+
+    value = _param_to_collection(value)
+
+    if isinstance(value, dict):
+        items = [(k, _param_to_str(v)) for k, v in value.items() if v is not None]
+
+        if style == "deepObject":
+            return [(f"{name}[{k}]", v) for k, v in items]
+
+        if explode:
+            return items
+
+        delimiter = {"spaceDelimited": " ", "pipeDelimited": "|"}.get(style, ",")
+
+        return [(name, delimiter.join(f"{k}{delimiter}{v}" for k, v in items))]
+
+    if isinstance(value, list):
+        if style == "form" and explode:
+            return [(name, _param_to_str(x)) for x in value]
+
+        delimiter = {"spaceDelimited": " ", "pipeDelimited": "|"}.get(style, ",")
+
+        return [(name, delimiter.join(_param_to_str(x) for x in value))]
+
+    return [(name, _param_to_str(value))]
+
+
+def _serialize_path_param(
+    name: str,
+    value: Any,
+    style: str,
+    explode: bool,
+) -> str:
+    """
+    Serialize a path parameter according to its OpenAPI `style` and `explode`
+
+    Values are percent-encoded so they can't change the meaning of the URL.
+    """
+
+    value = _param_to_collection(value)
+
+    if isinstance(value, dict):
+        pairs = [
+            (quote(str(k), safe=""), quote(_param_to_str(v), safe=""))
+            for k, v in value.items()
+            if v is not None
+        ]
+
+        if style == "matrix":
+            if explode:
+                return "".join(f";{k}={v}" for k, v in pairs)
+
+            return f";{name}=" + ",".join(f"{k},{v}" for k, v in pairs)
+
+        if explode:
+            joined = [f"{k}={v}" for k, v in pairs]
+        else:
+            joined = [x for pair in pairs for x in pair]
+
+        if style == "label":
+            return "." + ("." if explode else ",").join(joined)
+
+        return ",".join(joined)
+
+    if isinstance(value, list):
+        items = [quote(_param_to_str(x), safe="") for x in value]
+
+        if style == "matrix":
+            if explode:
+                return "".join(f";{name}={x}" for x in items)
+
+            return f";{name}=" + ",".join(items)
+
+        if style == "label":
+            return "." + ("." if explode else ",").join(items)
+
+        return ",".join(items)
+
+    encoded = quote(_param_to_str(value), safe="")
+
+    if style == "matrix":
+        return f";{name}={encoded}"
+
+    if style == "label":
+        return f".{encoded}"
+
+    return encoded
+
+
+def _serialize_header_param(
+    name: str,
+    value: Any,
+    explode: bool,
+) -> Dict[str, str]:
+    """
+    Serialize a header parameter, which always has the `simple` style
+    """
+
+    if value is None:
+        return {}
+
+    value = _param_to_collection(value)
+
+    if isinstance(value, dict):
+        pairs = [(str(k), _param_to_str(v)) for k, v in value.items() if v is not None]
+
+        if explode:
+            return {name: ",".join(f"{k}={v}" for k, v in pairs)}
+
+        return {name: ",".join(x for pair in pairs for x in pair)}
+
+    if isinstance(value, list):
+        return {name: ",".join(_param_to_str(x) for x in value)}
+
+    return {name: _param_to_str(value)}
@@ -0,0 +1,65 @@
+//! Parse a document into what code is generated from
+
+use std::collections::BTreeMap;
+
+use okapi::openapi3::OpenApi;
+
+use super::{module::security_schemes, Options};
+use crate::parse::{Diagnostics, Function, Functions, SecurityScheme};
+
+/// Everything code is generated from
+pub struct Prepared {
+    /// The document, without any schemas left out by tree shaking
+    pub openapi: OpenApi,
+
+    /// The functions to generate methods for
+    pub functions: Functions,
+
+    /// The security schemes the client can authenticate with
+    // TODO: remove this when more auth methods are supported
+    #[allow(clippy::zero_sized_map_values)]
+    pub security_schemes: BTreeMap<String, SecurityScheme>,
+
+    /// The name of the request model of each schema that needs one, see
+    /// [`request_models`](super::request_models)
+    pub request_models: BTreeMap<String, String>,
+}
+
+/// Parse the functions of a document and work out the models they need
+///
+/// Both the single module and the package are generated from this.
+pub fn prepare(
+    openapi: &OpenApi,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Prepared {
+    let security_schemes = security_schemes(openapi, diagnostics);
+
+    let mut functions = Function::try_from_paths(openapi, diagnostics);
+
+    let openapi = if options.tree_shake {
+        super::tree_shake(openapi, diagnostics)
+    } else {
+        openapi.clone()
+    };
+
+    let request_models = super::request_models(&openapi);
+    super::use_request_models(functions.values_mut(), &request_models);
+    super::paginate(
+        &openapi,
+        functions.values_mut(),
+        options.pagination.as_ref(),
+    );
+    super::drop_skipped_models(
+        &openapi,
+        functions.values_mut(),
+        &request_models,
+    );
+
+    Prepared {
+        openapi,
+        functions,
+        security_schemes,
+        request_models,
+    }
+}
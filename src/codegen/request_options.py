@@ -0,0 +1,33 @@
+class RequestOptions(TypedDict, total=False):
+    """
+    Settings for a single request, overriding those of the `ApiClient`
+
+    Attributes:
+        timeout: Seconds to wait for a response, or `None` to wait forever.
+            Leave it out to use the client's timeout.
+        max_retries: How many times a failed request is retried
+        backoff_factor: Seconds to wait before the first retry, doubling
+            with every retry after it
+        max_backoff: The most seconds to wait before a retry
+    """
+
+    timeout: Optional[float]
+    max_retries: int
+    backoff_factor: float
+    max_backoff: float
+
+
+BeforeRequestHook = Callable[[str, str, Dict[str, Any]], Awaitable[None]]
+"""
+Called with the method, URL, and keyword arguments of every attempt at a
+request before it's sent
+
+The keyword arguments are those passed to `aiohttp.ClientSession.request`, and
+can be changed in place, such as by adding to `headers`.
+"""
+
+AfterResponseHook = Callable[[aiohttp.ClientResponse], Awaitable[None]]
+"""
+Called with every response received, including those to requests that are
+then retried
+"""
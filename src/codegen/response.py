@@ -0,0 +1,62 @@
+_T = TypeVar("_T")
+_H = TypeVar("_H")
+_M = TypeVar("_M", bound=BaseModel)
+
+
+def _parse_headers(model: Type[_M], headers: Mapping[str, str]) -> _M:
+    """
+    Parse the headers of a response into a model of the headers it declares
+
+    Lists and sets are sent as comma-separated values. Headers that are missing
+    are left out, so they get the model's defaults.
+    """
+
+    values: Dict[str, Any] = {}
+
+    for field in model.__fields__.values():
+        value = headers.get(field.alias)
+
+        if value is None:
+            continue
+
+        if get_origin(field.outer_type_) in (list, set):
+            values[field.alias] = [x.strip() for x in value.split(",")]
+        else:
+            values[field.alias] = value
+
+    return model.parse_obj(values)
+
+
+class RawResponse(Generic[_T, _H]):
+    """
+    A parsed response along with the `aiohttp` response it came from
+
+    Attributes:
+        response: The `aiohttp` response, whose body has already been read
+        status: The HTTP status code of the response
+        body: The parsed body, as returned by the method without
+            `_with_raw_response`
+    """
+
+    def __init__(
+        self,
+        response: aiohttp.ClientResponse,
+        body: _T,
+        headers: Callable[[], _H],
+    ) -> None:
+        self.response = response
+        self.status = response.status
+        self.body = body
+        self._headers = headers
+
+    @property
+    def headers(self) -> _H:
+        """
+        The headers the document declares for the response, parsed into a model
+
+        This is `None` for operations that don't declare any headers. Every
+        header is in `response.headers`. Headers are parsed when this is read,
+        so invalid headers only raise an error here.
+        """
+
+        return self._headers()
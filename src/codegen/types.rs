@@ -1,92 +1,296 @@
 //! Generate types defined by an OpenAPI spec
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use okapi::openapi3::OpenApi;
 
-use crate::parse::Type;
+use super::graph::{is_cyclic, strongly_connected_components, Graph};
+use crate::parse::{
+    docs, Access, Diagnostics, Error, ErrorKind, Field, Function, Location,
+    Struct, Type,
+};
 
 /// Convert an OpenAPI document to its type definitions
-pub fn types(openapi: &OpenApi) -> String {
-    let components = if let Some(x) = &openapi.components {
-        x
-    } else {
-        todo!()
+///
+/// Schemas that can't be converted are left out and recorded in
+/// `diagnostics`. Schemas in `request_models` get a second model for request
+/// bodies, see [`request_models`](request_models).
+pub fn types(
+    openapi: &OpenApi,
+    request_models: &BTreeMap<String, String>,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    // Documents without components are valid, they just have no models
+    let Some(components) = &openapi.components else {
+        return String::new();
     };
 
-    let mut code = String::new();
+    let mut types = BTreeMap::new();
 
     for (name, object) in &components.schemas {
-        let r#type = match Type::try_from(object) {
-            Ok(x) => x,
+        match Type::try_from(object) {
+            Ok(x) => {
+                types.insert(name.as_str(), x);
+            }
             Err(e) => {
-                eprintln!(
-                    "error: failed to parse {name}: {}\nbacktrace:\n{:?}",
-                    e.kind, e.backtrace
-                );
-
-                continue;
+                diagnostics.push(e.within(["components", "schemas", name]));
             }
-        };
+        }
+    }
 
-        code.push_str("class ");
-        code.push_str(name);
-        code.push_str("(_BaseModel):\n");
-
-        if let Type::Struct(s) = r#type {
-            // Struct documentation
-            if let Some(docs) = s.docs.as_ref() {
-                code.push_str(super::INDENT);
-                code.push_str(r#"""""#);
-                code.push_str(docs);
-                code.push_str(r#"""""#);
-                code.push_str("\n\n");
-            }
+    // Every model to generate, with only the fields it keeps
+    let mut models = BTreeMap::new();
 
-            for (name, data) in &s.fields {
-                code.push_str(super::INDENT);
-                code.push_str(name);
-                code.push_str(": ");
-                code.push_str(&type_to_string(&data.r#type, true));
+    for (&name, r#type) in &types {
+        // Models that failed to parse aren't generated, so anything can go in
+        // their place
+        let r#type = replace_refs(r#type, &|x| {
+            (!types.contains_key(x)).then_some(Type::Any)
+        });
 
-                if matches!(data.r#type, Type::Option(_)) {
-                    code.push_str(" = Field(default=None, ");
-                } else {
-                    code.push_str(" = Field(default=..., ");
-                }
+        // Requests never contain read-only fields, and refer to the request
+        // models of other types
+        if let Some(request_name) = request_models.get(name) {
+            models.insert(
+                request_name.as_str(),
+                retain_fields(&request_type(&r#type, request_models), |x| {
+                    x.access != Access::ReadOnly
+                }),
+            );
+        }
 
-                if data.deprecated {
-                    code.push_str("deprecated=True, ");
-                }
+        // Responses never contain write-only fields
+        models.insert(
+            name,
+            retain_fields(&r#type, |x| x.access != Access::WriteOnly),
+        );
+    }
 
-                // Pydantic field documentation
-                if let Some(docs) = data.docs.as_ref() {
-                    code.push_str(r#"description="""""#);
-                    code.push_str(docs);
-                    code.push_str(r#"""", "#);
-                }
+    let graph: Graph<'_> = models
+        .iter()
+        .map(|(name, x)| {
+            let references = x
+                .references()
+                .into_iter()
+                .filter(|x| models.contains_key(x))
+                .collect();
+
+            (*name, references)
+        })
+        .collect();
+
+    let mut code = String::new();
+
+    // Models are generated after the models they refer to, so references only
+    // have to be deferred within a cycle
+    for component in strongly_connected_components(&graph) {
+        let cyclic = is_cyclic(&graph, &component);
+
+        let deferred = |x: &str| cyclic && component.contains(&x);
+
+        for name in &component {
+            if let Some(Type::Struct(s)) = models.get(name) {
+                code.push_str(&class(name, s, &deferred));
+            }
+        }
+
+        // Aliases go after the classes in their cycle, since the references
+        // within an alias are never resolved once quoted
+        let is_struct =
+            |x: &str| matches!(models.get(x), Some(Type::Struct(_)));
+        let alias_deferred = |x: &str| deferred(x) && !is_struct(x);
 
-                code.push_str(")\n");
+        for name in &component {
+            match models.get(name) {
+                Some(Type::Struct(_)) | None => {}
+                Some(r#type) => {
+                    let docs = components.schemas.get(*name).and_then(docs);
+                    code.push_str(&alias(name, r#type, docs, &alias_deferred));
+                }
+            }
+        }
 
-                // Field documentation
-                if let Some(docs) = data.docs.as_ref() {
-                    code.push_str(super::INDENT);
-                    code.push_str(r#"""""#);
-                    code.push_str(docs);
-                    code.push_str(r#"""""#);
-                    code.push('\n');
+        // Pydantic does not like deferred type signatures, this dynamically
+        // un-defers them once every model in the cycle exists. Aliases are
+        // resolved through the classes that use them.
+        if cyclic {
+            for name in &component {
+                if is_struct(name) {
+                    code.push_str(name);
+                    code.push_str(".update_forward_refs()\n");
                 }
             }
-        } else {
-            code.push_str("pass\n");
+
+            code.push_str("\n\n");
         }
+    }
+
+    code
+}
+
+/// Names of the models generated by [`types`](types) and
+/// [`header_models`](header_models)
+pub fn model_names<'a, I>(
+    openapi: &OpenApi,
+    request_models: &BTreeMap<String, String>,
+    fs: I,
+) -> Vec<String>
+where
+    I: IntoIterator<Item = (&'a (String, String), &'a Function)>,
+{
+    // Schemas that fail to parse are left out by `types`
+    let schemas = openapi
+        .components
+        .iter()
+        .flat_map(|x| &x.schemas)
+        .filter(|(_, x)| Type::try_from(*x).is_ok())
+        .map(|(name, _)| name.clone());
+
+    let headers = fs
+        .into_iter()
+        .filter(|(_, x)| x.response_headers.is_some())
+        .map(|((method, path), _)| super::header_model(method, path));
+
+    schemas.chain(request_models.values().cloned()).chain(headers).collect()
+}
+
+/// Generate a model for the response headers of each function that declares
+/// any
+///
+/// These refer to models from [`types`](types), so they go after them.
+pub fn header_models<'a, I>(fs: I) -> String
+where
+    I: IntoIterator<Item = (&'a (String, String), &'a Function)>,
+{
+    let mut code = String::new();
+
+    for ((method, path), function) in fs {
+        let Some(headers) = &function.response_headers else {
+            continue;
+        };
+
+        let mut headers = headers.clone();
+        headers.set_docs(format!(
+            "The headers of a response from `{}`",
+            super::functions::name(method, path)
+        ));
+
+        code.push_str(&class(
+            &super::header_model(method, path),
+            &headers,
+            &|_| false,
+        ));
+    }
+
+    code
+}
+
+/// Keep only the fields of a struct that pass `filter`
+fn retain_fields<F>(r#type: &Type, filter: F) -> Type
+where
+    F: Fn(&Field) -> bool,
+{
+    let mut r#type = r#type.clone();
+
+    if let Type::Struct(s) = &mut r#type {
+        s.fields.retain(|_, x| filter(x));
+    }
+
+    r#type
+}
+
+/// Generate a type alias for a schema that isn't an object with properties,
+/// like `Name = str`
+///
+/// References to the models for which `deferred` returns `true` are quoted.
+fn alias<F>(
+    name: &str,
+    r#type: &Type,
+    docs: Option<String>,
+    deferred: &F,
+) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    let mut code = format!("{name} = {}\n", annotation(r#type, deferred));
+
+    if let Some(docs) = docs {
+        code.push_str(&super::docstring(&docs, ""));
+        code.push('\n');
+    }
+
+    code.push_str("\n\n");
+
+    code
+}
+
+/// Generate a model class
+///
+/// References to the models for which `deferred` returns `true` are quoted.
+fn class<F>(name: &str, s: &Struct, deferred: &F) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    let mut code = String::new();
 
+    code.push_str("class ");
+    code.push_str(name);
+    code.push_str("(_BaseModel):\n");
+
+    // Struct documentation
+    if let Some(docs) = s.docs.as_ref() {
+        code.push_str(&super::docstring(docs, super::INDENT));
         code.push_str("\n\n");
     }
 
-    // Pydantic does not like deferred type signatures, this dynamically
-    // un-defers them.
-    for (name, ..) in &components.schemas {
-        code.push_str(name);
-        code.push_str(".update_forward_refs()\n");
+    if s.fields.is_empty() && s.docs.is_none() {
+        code.push_str(super::INDENT);
+        code.push_str("pass\n");
+    }
+
+    for (name, data) in &s.fields {
+        let (attribute, alias) = attribute(name);
+
+        code.push_str(super::INDENT);
+        code.push_str(&attribute);
+        code.push_str(": ");
+        code.push_str(&annotation(&data.r#type, deferred));
+
+        // Nullable fields still have to be present if they're required
+        match &data.default {
+            _ if data.required => code.push_str(" = Field(default=..., "),
+            Some(x) if super::is_mutable(x) => code.push_str(&format!(
+                " = Field(default_factory=lambda: {}, ",
+                super::literal(x)
+            )),
+            Some(x) => code
+                .push_str(&format!(" = Field(default={}, ", super::literal(x))),
+            None => code.push_str(" = Field(default=None, "),
+        }
+
+        if let Some(alias) = alias {
+            code.push_str(&format!("alias={}, ", super::string_literal(alias)));
+        }
+
+        if data.deprecated {
+            code.push_str("deprecated=True, ");
+        }
+
+        // Pydantic field documentation
+        if let Some(docs) = data.docs.as_ref() {
+            code.push_str(&format!(
+                "description={}, ",
+                super::string_literal(docs)
+            ));
+        }
+
+        code.push_str(")\n");
+
+        // Field documentation
+        if let Some(docs) = data.docs.as_ref() {
+            code.push_str(&super::docstring(docs, super::INDENT));
+            code.push('\n');
+        }
     }
 
     code.push_str("\n\n");
@@ -94,12 +298,239 @@ pub fn types(openapi: &OpenApi) -> String {
     code
 }
 
+/// Choose the Python attribute name for a property, and the alias it's
+/// serialized as if that's different
+///
+/// Pydantic ignores attributes starting with an underscore, so those are
+/// renamed too.
+fn attribute(name: &str) -> (String, Option<&str>) {
+    if super::is_identifier(name) && !name.starts_with('_') {
+        return (name.to_owned(), None);
+    }
+
+    let mut attribute = super::identifier(name);
+
+    if attribute.starts_with('_') {
+        attribute.insert_str(0, "field");
+    }
+
+    (attribute, Some(name))
+}
+
+/// Leave out the component schemas that no operation uses, directly or
+/// through other schemas
+///
+/// Every schema that's left out is recorded in `diagnostics`.
+pub fn tree_shake(openapi: &OpenApi, diagnostics: &mut Diagnostics) -> OpenApi {
+    let used = crate::filter::reachable_schemas(openapi);
+
+    let mut openapi = openapi.clone();
+
+    let Some(components) = &mut openapi.components else {
+        return openapi;
+    };
+
+    components.schemas.retain(|name, _| {
+        let keep = used.contains(name);
+
+        if !keep {
+            diagnostics.note(Error::from(ErrorKind::UnusedSchema).within([
+                "components",
+                "schemas",
+                name,
+            ]));
+        }
+
+        keep
+    });
+
+    openapi
+}
+
+/// Find the schemas that need a separate model for request bodies
+///
+/// These are the object schemas with read-only or write-only properties, and
+/// those that refer to them. The returned map is from the name of the schema
+/// to the name of its request model, such as `Call` to `CallCreate`.
+pub fn request_models(openapi: &OpenApi) -> BTreeMap<String, String> {
+    let Some(components) = &openapi.components else {
+        return BTreeMap::new();
+    };
+
+    // Schemas that fail to parse are reported by `types`
+    let types: BTreeMap<&str, Type> = components
+        .schemas
+        .iter()
+        .filter_map(|(name, x)| Some((name.as_str(), Type::try_from(x).ok()?)))
+        .collect();
+
+    let mut needed: BTreeSet<&str> = types
+        .iter()
+        .filter(|(_, x)| match x {
+            Type::Struct(s) => {
+                s.fields.values().any(|x| x.access != Access::ReadWrite)
+            }
+            _ => false,
+        })
+        .map(|(name, _)| *name)
+        .collect();
+
+    // Keep going until nothing else refers to a schema that needs a request
+    // model
+    loop {
+        let more: Vec<&str> = types
+            .iter()
+            .filter(|(name, x)| {
+                matches!(x, Type::Struct(_))
+                    && !needed.contains(*name)
+                    && x.references().iter().any(|x| needed.contains(x))
+            })
+            .map(|(name, _)| *name)
+            .collect();
+
+        if more.is_empty() {
+            break;
+        }
+
+        needed.extend(more);
+    }
+
+    needed
+        .into_iter()
+        .map(|name| {
+            let mut request_name = format!("{name}Create");
+
+            while components.schemas.contains_key(&request_name) {
+                request_name.push_str("Request");
+            }
+
+            (name.to_owned(), request_name)
+        })
+        .collect()
+}
+
+/// Make request bodies use request models instead of response models
+pub fn use_request_models<'a, I>(
+    fs: I,
+    request_models: &BTreeMap<String, String>,
+) where
+    I: IntoIterator<Item = &'a mut Function>,
+{
+    let arguments = fs
+        .into_iter()
+        .flat_map(|x| x.arguments.iter_mut())
+        .filter(|x| x.location == Location::Body);
+
+    for argument in arguments {
+        argument.r#type = request_type(&argument.r#type, request_models);
+    }
+}
+
+/// Replace the references to component schemas that failed to parse with
+/// [`Type::Any`](Type::Any)
+///
+/// [`types`](types) leaves these models out and reports why, so nothing may
+/// refer to them. Request models are known too, so this can run after
+/// [`use_request_models`](use_request_models).
+pub fn drop_skipped_models<'a, I>(
+    openapi: &OpenApi,
+    fs: I,
+    request_models: &BTreeMap<String, String>,
+) where
+    I: IntoIterator<Item = &'a mut Function>,
+{
+    let known: BTreeSet<&str> = openapi
+        .components
+        .iter()
+        .flat_map(|x| &x.schemas)
+        .filter(|(_, x)| Type::try_from(*x).is_ok())
+        .map(|(name, _)| name.as_str())
+        .chain(request_models.values().map(String::as_str))
+        .collect();
+
+    let replace = |x: &str| (!known.contains(x)).then_some(Type::Any);
+
+    for f in fs {
+        for argument in &mut f.arguments {
+            argument.r#type = replace_refs(&argument.r#type, &replace);
+        }
+
+        for r#type in f.responses.values_mut() {
+            *r#type = replace_refs(r#type, &replace);
+        }
+
+        if let Some(headers) = &mut f.response_headers {
+            for field in headers.fields.values_mut() {
+                field.r#type = replace_refs(&field.r#type, &replace);
+            }
+        }
+
+        if let Some(pagination) = &mut f.pagination {
+            pagination.item = replace_refs(&pagination.item, &replace);
+        }
+    }
+}
+
+/// Make a type refer to the request models of the types it contains
+fn request_type(
+    r#type: &Type,
+    request_models: &BTreeMap<String, String>,
+) -> Type {
+    replace_refs(r#type, &|x| {
+        request_models
+            .get(x)
+            .map(|x| Type::Ref(format!("#/components/schemas/{x}")))
+    })
+}
+
+/// Replace the references within a type for which `replace` returns a type,
+/// given the name of the schema they refer to
+fn replace_refs<F>(r#type: &Type, replace: &F) -> Type
+where
+    F: Fn(&str) -> Option<Type>,
+{
+    let recurse = |x: &Type| Box::new(replace_refs(x, replace));
+
+    match r#type {
+        Type::Ref(x) => {
+            let name = x.rsplit_once('/').map_or(x.as_str(), |(_, x)| x);
+
+            replace(name).unwrap_or_else(|| r#type.clone())
+        }
+        Type::Option(x) => Type::Option(recurse(x)),
+        Type::List(x) => Type::List(recurse(x)),
+        Type::Set(x) => Type::Set(recurse(x)),
+        Type::Tuple(xs) => {
+            Type::Tuple(xs.iter().map(|x| replace_refs(x, replace)).collect())
+        }
+        Type::Struct(s) => {
+            let mut s = s.clone();
+
+            for field in s.fields.values_mut() {
+                field.r#type = replace_refs(&field.r#type, replace);
+            }
+
+            Type::Struct(s)
+        }
+        _ => r#type.clone(),
+    }
+}
+
 /// Generate a field's type
 ///
 /// The `deferred` argument indicates whether references should be quoted.
 /// Quoting can be necessary in Python when a type depends on another type that
 /// hasn't yet been defined.
 pub fn type_to_string(ty: &Type, deferred: bool) -> String {
+    annotation(ty, &|_| deferred)
+}
+
+/// Generate a field's type, quoting the references to the models for which
+/// `deferred` returns `true`
+fn annotation<F>(ty: &Type, deferred: &F) -> String
+where
+    F: Fn(&str) -> bool,
+{
     match ty {
         // Simple types
         Type::String => "str".into(),
@@ -111,7 +542,22 @@ pub fn type_to_string(ty: &Type, deferred: bool) -> String {
 
         Type::Option(ty) => {
             let mut x = "Optional[".to_owned();
-            x.push_str(&type_to_string(ty, deferred));
+            x.push_str(&annotation(ty, deferred));
+            x.push(']');
+
+            x
+        }
+
+        Type::Literal(x) => format!("Literal[{}]", super::literal(x)),
+
+        Type::Tuple(tys) => {
+            let mut x = "Tuple[".to_owned();
+            x.push_str(
+                &tys.iter()
+                    .map(|ty| annotation(ty, deferred))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
             x.push(']');
 
             x
@@ -119,7 +565,7 @@ pub fn type_to_string(ty: &Type, deferred: bool) -> String {
 
         Type::List(ty) => {
             let mut x = "List[".to_owned();
-            x.push_str(&type_to_string(ty, deferred));
+            x.push_str(&annotation(ty, deferred));
             x.push(']');
 
             x
@@ -127,7 +573,7 @@ pub fn type_to_string(ty: &Type, deferred: bool) -> String {
 
         Type::Set(ty) => {
             let mut x = "Set[".to_owned();
-            x.push_str(&type_to_string(ty, deferred));
+            x.push_str(&annotation(ty, deferred));
             x.push(']');
 
             x
@@ -144,18 +590,76 @@ pub fn type_to_string(ty: &Type, deferred: bool) -> String {
 
         // This is a reference to another type
         Type::Ref(x) => {
-            // This seems... cursed
-            let name = x
-                .rsplit('/')
-                .next()
-                .expect("invalid reference name")
-                .to_owned();
-
-            if deferred {
-                format!(r#""{name}""#,)
+            // Parsing guarantees this is a `#/components/schemas/` reference
+            let name = x.rsplit_once('/').map_or(x.as_str(), |(_, x)| x);
+
+            if deferred(name) {
+                format!(r#""{name}""#)
             } else {
-                name
+                name.to_owned()
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use okapi::openapi3::OpenApi;
+
+    use super::types;
+    use crate::parse::Diagnostics;
+
+    /// Generate the models of the schemas in a YAML `components/schemas`
+    fn models(schemas: &str) -> String {
+        let openapi: OpenApi = serde_yaml::from_str(&format!(
+            "{{openapi: 3.0.0, info: {{title: T, version: '1'}}, paths: {{}}, \
+             components: {{schemas: {schemas}}}}}"
+        ))
+        .unwrap_or_else(|e| panic!("{e}"));
+
+        types(&openapi, &BTreeMap::new(), &mut Diagnostics::default())
+    }
+
+    /// References are only quoted within a cycle, which is resolved once all
+    /// of its models exist
+    #[test]
+    fn defers_references_within_cycles() {
+        let code = models(
+            "{
+                A: {type: object, properties: {b: {$ref: \
+             '#/components/schemas/B'}}},
+                B: {type: object, properties: {
+                    a: {$ref: '#/components/schemas/A'},
+                    c: {$ref: '#/components/schemas/C'},
+                }},
+                C: {type: object, properties: {x: {type: string}}},
+            }",
+        );
+
+        let position = |x: &str| code.find(x).unwrap_or(usize::MAX);
+
+        assert!(position("class C(") < position("class A("));
+        assert!(code.contains("b: Optional[\"B\"]"));
+        assert!(code.contains("a: Optional[\"A\"]"));
+        assert!(code.contains("c: Optional[C]"));
+        assert!(
+            code.contains("A.update_forward_refs()\nB.update_forward_refs()")
+        );
+        assert!(!code.contains("C.update_forward_refs()"));
+    }
+
+    /// Models referring only to themselves are deferred too
+    #[test]
+    fn defers_self_references() {
+        let code = models(
+            "{Node: {type: object, properties: {
+                next: {$ref: '#/components/schemas/Node'},
+            }}}",
+        );
+
+        assert!(code.contains("next: Optional[\"Node\"]"));
+        assert!(code.contains("Node.update_forward_refs()"));
+    }
+}
@@ -1,35 +1,250 @@
 //! Where to begin to converting OpenAPI to source code
 
-use std::error::Error as StdError;
+use std::{
+    collections::BTreeMap,
+    error::Error as StdError,
+    path::{Path, PathBuf},
+};
 
 use okapi::openapi3::OpenApi;
+use serde_json::Value;
+
+use crate::{
+    bundle::bundle, codegen::Options, filter::Filter, parse::Diagnostics,
+    swagger,
+};
+
+/// Formats an OpenAPI document can be written in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// YAML
+    Yaml,
+
+    /// JSON
+    Json,
+}
+
+impl Format {
+    /// Determine the format of a document from its file extension
+    ///
+    /// Returns `None` if the extension is missing or unrecognized.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Determine the format of a document from its contents
+    ///
+    /// An OpenAPI document is always an object, so a JSON document must start
+    /// with `{`. Anything else is assumed to be YAML.
+    pub fn detect(s: &str) -> Self {
+        if s.trim_start().starts_with('{') {
+            Self::Json
+        } else {
+            Self::Yaml
+        }
+    }
+}
+
+/// Convert YAML or JSON into a string containing source code
+///
+/// The format is detected from the contents of `s`. See
+/// [`from_yaml`](from_yaml) for the meaning of `path`,
+/// `filter`, and `options`.
+///
+/// # Errors
+///
+/// This function will fail if `s` is not a valid OpenAPI document, or if a file
+/// it refers to can't be read.
+pub fn from_str(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<String, Box<dyn StdError>> {
+    match Format::detect(s) {
+        Format::Yaml => from_yaml(s, path, filter, options, diagnostics),
+        Format::Json => from_json(s, path, filter, options, diagnostics),
+    }
+}
 
 /// Convert YAML into a string containing source code
 ///
+/// `path` is where `s` was read from; `$ref`s to other files are resolved
+/// relative to it. Only the operations `filter` accepts are generated, as
+/// configured by `options`. Problems with individual parts of the document are
+/// recorded in `diagnostics`.
+///
 /// # Errors
 ///
 /// This function will fail if `s` is not a valid YAML-formatted OpenAPI
-/// document.
-pub fn from_yaml(s: &str) -> Result<String, Box<dyn StdError>> {
-    let t = serde_yaml::from_str(s)?;
-
-    Ok(from_openapi(&t))
+/// document, or if a file it refers to can't be read.
+pub fn from_yaml(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<String, Box<dyn StdError>> {
+    Ok(from_openapi(&parse_yaml(s, path, filter)?, options, diagnostics))
 }
 
 /// Convert JSON into a string containing source code
 ///
+/// See [`from_yaml`](from_yaml) for the meaning of `path`,
+/// `filter`, and `options`.
+///
 /// # Errors
 ///
 /// This function will fail if `s` is not a valid JSON-formatted OpenAPI
-/// document.
-pub fn from_json(s: &str) -> Result<String, Box<dyn StdError>> {
-    let t = serde_json::from_str(s)?;
+/// document, or if a file it refers to can't be read.
+pub fn from_json(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<String, Box<dyn StdError>> {
+    Ok(from_openapi(&parse_json(s, path, filter)?, options, diagnostics))
+}
+
+/// Parse a YAML-formatted OpenAPI document and the files it refers to
+fn parse_yaml(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+) -> Result<OpenApi, Box<dyn StdError>> {
+    parse(serde_yaml::from_str(s)?, path, filter)
+}
+
+/// Parse a JSON-formatted OpenAPI document and the files it refers to
+fn parse_json(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+) -> Result<OpenApi, Box<dyn StdError>> {
+    parse(serde_json::from_str(s)?, path, filter)
+}
 
-    Ok(from_openapi(&t))
+/// Turn a deserialized document into OpenAPI, converting it from Swagger 2.0
+/// first if necessary, and leave out whatever `filter` rejects
+fn parse(
+    document: Value,
+    path: &Path,
+    filter: &Filter,
+) -> Result<OpenApi, Box<dyn StdError>> {
+    let document = if swagger::is_swagger(&document) {
+        swagger::convert(&document)
+    } else {
+        document
+    };
+
+    let mut openapi = serde_json::from_value(bundle(document, path)?)?;
+    filter.apply(&mut openapi);
+
+    Ok(openapi)
 }
 
 /// Converts an OpenAPI specification into a string containing source code
+///
+/// Problems with individual parts of the document are recorded in
+/// `diagnostics`.
+#[must_use = "It's pointless to call this function unless you use the result"]
+pub fn from_openapi(
+    openapi: &OpenApi,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    crate::codegen::module(openapi, options, diagnostics)
+}
+
+/// Convert YAML or JSON into the files of a Python package
+///
+/// The format is detected from the contents of `s`. See
+/// [`from_yaml`](from_yaml) for the meaning of `path`,
+/// `filter`, and `options`.
+///
+/// # Errors
+///
+/// This function will fail if `s` is not a valid OpenAPI document, or if a file
+/// it refers to can't be read.
+pub fn package_from_str(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<BTreeMap<PathBuf, String>, Box<dyn StdError>> {
+    match Format::detect(s) {
+        Format::Yaml => {
+            package_from_yaml(s, path, filter, options, diagnostics)
+        }
+        Format::Json => {
+            package_from_json(s, path, filter, options, diagnostics)
+        }
+    }
+}
+
+/// Convert YAML into the files of a Python package
+///
+/// See [`from_yaml`](from_yaml) for the meaning of `path`,
+/// `filter`, and `options`.
+///
+/// # Errors
+///
+/// This function will fail if `s` is not a valid YAML-formatted OpenAPI
+/// document, or if a file it refers to can't be read.
+pub fn package_from_yaml(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<BTreeMap<PathBuf, String>, Box<dyn StdError>> {
+    Ok(package_from_openapi(
+        &parse_yaml(s, path, filter)?,
+        options,
+        diagnostics,
+    ))
+}
+
+/// Convert JSON into the files of a Python package
+///
+/// See [`from_yaml`](from_yaml) for the meaning of `path`,
+/// `filter`, and `options`.
+///
+/// # Errors
+///
+/// This function will fail if `s` is not a valid JSON-formatted OpenAPI
+/// document, or if a file it refers to can't be read.
+pub fn package_from_json(
+    s: &str,
+    path: &Path,
+    filter: &Filter,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Result<BTreeMap<PathBuf, String>, Box<dyn StdError>> {
+    Ok(package_from_openapi(
+        &parse_json(s, path, filter)?,
+        options,
+        diagnostics,
+    ))
+}
+
+/// Converts an OpenAPI specification into the files of a Python package
+///
+/// The keys of the returned map are paths relative to the directory the
+/// package should be written to. Problems with individual parts of the
+/// document are recorded in `diagnostics`.
 #[must_use = "It's pointless to call this function unless you use the result"]
-pub fn from_openapi(openapi: &OpenApi) -> String {
-    crate::codegen::module(openapi)
+pub fn package_from_openapi(
+    openapi: &OpenApi,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> BTreeMap<PathBuf, String> {
+    crate::codegen::package(openapi, options, diagnostics)
 }
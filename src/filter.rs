@@ -0,0 +1,272 @@
+//! Leave parts of an OpenAPI document out of the generated code
+
+use std::collections::BTreeSet;
+
+use glob::{MatchOptions, Pattern};
+use okapi::openapi3::{OpenApi, Operation, PathItem};
+use serde_json::Value;
+
+/// Which parts of an OpenAPI document code should be generated for
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    /// Leave out operations marked as deprecated
+    pub omit_deprecated: bool,
+
+    /// Operations to generate code for
+    ///
+    /// If this is empty, every operation is included. Otherwise, an operation
+    /// has to match at least one of each kind of criterion given, so
+    /// `--include-tag Calls --include-method get` only includes `GET`
+    /// operations tagged `Calls`.
+    pub include: Selector,
+
+    /// Operations to leave out, even if they're included
+    ///
+    /// An operation matching any of these criteria is left out.
+    pub exclude: Selector,
+}
+
+/// Criteria an operation can be selected by
+#[derive(Debug, Default, Clone)]
+pub struct Selector {
+    /// Tags, any of which the operation has
+    pub tags: Vec<String>,
+
+    /// Globs matching the operation's path, like `/v2/calls/**`
+    pub paths: Vec<Pattern>,
+
+    /// HTTP methods, compared case-insensitively
+    pub methods: Vec<String>,
+
+    /// Operation IDs
+    pub operation_ids: Vec<String>,
+
+    /// Names of specification extensions, like `x-internal`, that the
+    /// operation sets to something other than `false` or `null`
+    pub extensions: Vec<String>,
+}
+
+/// How path globs are matched
+///
+/// `*` doesn't match across `/`, but `**` does.
+const PATH_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl Filter {
+    /// Remove every operation this filter rejects from `openapi`
+    ///
+    /// Paths left without any operations are removed too. If anything was
+    /// removed, so are the component schemas that nothing left refers to.
+    pub fn apply(&self, openapi: &mut OpenApi) {
+        let mut removed = false;
+
+        for (path, item) in &mut openapi.paths {
+            for (method, operation) in operations(item) {
+                if operation
+                    .as_ref()
+                    .is_some_and(|x| !self.accepts(method, path, x))
+                {
+                    *operation = None;
+                    removed = true;
+                }
+            }
+        }
+
+        if !removed {
+            return;
+        }
+
+        openapi
+            .paths
+            .retain(|_, item| operations(item).any(|(_, x)| x.is_some()));
+
+        let reachable = reachable_schemas(openapi);
+
+        if let Some(components) = &mut openapi.components {
+            components.schemas.retain(|name, _| reachable.contains(name));
+        }
+    }
+
+    /// Whether code should be generated for an operation
+    fn accepts(&self, method: &str, path: &str, operation: &Operation) -> bool {
+        !(self.omit_deprecated && operation.deprecated)
+            && self.include.all(method, path, operation)
+            && !self.exclude.any(method, path, operation)
+    }
+}
+
+impl Selector {
+    /// Whether the operation meets every kind of criterion given
+    fn all(&self, method: &str, path: &str, operation: &Operation) -> bool {
+        self.matches(method, path, operation).all(|x| x.unwrap_or(true))
+    }
+
+    /// Whether the operation meets any of the criteria given
+    fn any(&self, method: &str, path: &str, operation: &Operation) -> bool {
+        self.matches(method, path, operation).any(|x| x.unwrap_or(false))
+    }
+
+    /// Whether the operation matches each kind of criterion, or `None` for
+    /// kinds without any criteria
+    fn matches<'a>(
+        &'a self,
+        method: &'a str,
+        path: &'a str,
+        operation: &'a Operation,
+    ) -> impl Iterator<Item = Option<bool>> + 'a {
+        /// `None` if there are no criteria, otherwise whether any is met
+        fn check<T>(xs: &[T], f: impl Fn(&T) -> bool) -> Option<bool> {
+            (!xs.is_empty()).then(|| xs.iter().any(f))
+        }
+
+        [
+            check(&self.tags, |x| operation.tags.contains(x)),
+            check(&self.paths, |x| x.matches_with(path, PATH_MATCH_OPTIONS)),
+            check(&self.methods, |x| x.eq_ignore_ascii_case(method)),
+            check(&self.operation_ids, |x| {
+                operation.operation_id.as_ref() == Some(x)
+            }),
+            check(&self.extensions, |x| {
+                operation.extensions.get(x).is_some_and(|x| {
+                    !matches!(x, Value::Null | Value::Bool(false))
+                })
+            }),
+        ]
+        .into_iter()
+    }
+}
+
+/// Find the names of the component schemas that operations refer to, directly
+/// or through other components
+///
+/// Components are only followed once something in `paths` reaches them, so
+/// schemas used by nothing but unused parameters, responses, and so on aren't
+/// reachable.
+pub fn reachable_schemas(openapi: &OpenApi) -> BTreeSet<String> {
+    let Ok(document) = serde_json::to_value(openapi) else {
+        // Everything is reachable if we can't tell otherwise
+        return openapi
+            .components
+            .iter()
+            .flat_map(|x| x.schemas.keys().cloned())
+            .collect();
+    };
+
+    let mut pending = Vec::new();
+
+    if let Some(paths) = document.get("paths") {
+        references(paths, &mut pending);
+    }
+
+    let mut reached = BTreeSet::new();
+
+    while let Some(pointer) = pending.pop() {
+        if reached.contains(&pointer) {
+            continue;
+        }
+
+        if let Some(component) = document.pointer(&pointer) {
+            references(component, &mut pending);
+        }
+
+        reached.insert(pointer);
+    }
+
+    reached
+        .iter()
+        .filter_map(|x| x.strip_prefix("/components/schemas/"))
+        .map(|x| x.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Collect the JSON pointers of the components referred to within `value`,
+/// like `/components/schemas/Call`
+///
+/// References to something within a component count as references to the
+/// whole component.
+fn references(value: &Value, pointers: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            let component = object
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|x| x.strip_prefix("#/components/"))
+                .and_then(|x| {
+                    let mut segments = x.split('/');
+                    Some((segments.next()?, segments.next()?))
+                });
+
+            if let Some((kind, name)) = component {
+                pointers.push(format!("/components/{kind}/{name}"));
+            }
+
+            for child in object.values() {
+                references(child, pointers);
+            }
+        }
+        Value::Array(array) => {
+            for child in array {
+                references(child, pointers);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Every operation slot of a path item, paired with its HTTP method
+fn operations(
+    item: &mut PathItem,
+) -> impl Iterator<Item = (&'static str, &mut Option<Operation>)> {
+    [
+        ("get", &mut item.get),
+        ("put", &mut item.put),
+        ("post", &mut item.post),
+        ("delete", &mut item.delete),
+        ("options", &mut item.options),
+        ("head", &mut item.head),
+        ("patch", &mut item.patch),
+        ("trace", &mut item.trace),
+    ]
+    .into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use okapi::openapi3::OpenApi;
+
+    use super::reachable_schemas;
+
+    /// Schemas are reachable through the components operations use, but not
+    /// through unused ones
+    #[test]
+    fn follows_used_components() {
+        let openapi: OpenApi = serde_yaml::from_str(
+            r"
+openapi: 3.0.0
+info: {title: T, version: '1'}
+paths:
+  /a:
+    get:
+      parameters: [$ref: '#/components/parameters/Used']
+      responses: {'204': {description: ok}}
+components:
+  parameters:
+    Used: {name: u, in: query, schema: {$ref: '#/components/schemas/A'}}
+    Unused: {name: x, in: query, schema: {$ref: '#/components/schemas/C'}}
+  schemas:
+    A: {type: array, items: {$ref: '#/components/schemas/B/properties/b'}}
+    B: {type: object, properties: {b: {type: string}}}
+    C: {type: string}
+",
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+
+        assert_eq!(
+            reachable_schemas(&openapi).into_iter().collect::<Vec<_>>(),
+            ["A", "B"]
+        );
+    }
+}
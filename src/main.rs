@@ -16,24 +16,40 @@
 #![warn(clippy::str_to_string)]
 #![warn(clippy::string_add)]
 #![warn(clippy::string_slice)]
-#![warn(clippy::string_to_string)]
 #![warn(clippy::undocumented_unsafe_blocks)]
 #![warn(clippy::unneeded_field_pattern)]
 #![warn(clippy::unseparated_literal_suffix)]
 #![warn(clippy::unwrap_used)]
 #![warn(clippy::wildcard_dependencies)]
+#![allow(clippy::format_push_string)]
 
 use std::{
+    collections::BTreeMap,
     error::Error as StdError,
+    fs::File,
     io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
 };
 
 use clap::StructOpt;
+use entrypoint::Format;
 
 mod args;
+mod bundle;
 mod codegen;
 mod entrypoint;
+mod filter;
 mod parse;
+mod swagger;
+
+/// Generated code, ready to be written out
+enum Output {
+    /// A single Python module
+    Module(String),
+
+    /// Files making up a Python package, keyed by their relative paths
+    Package(BTreeMap<PathBuf, String>),
+}
 
 fn main() -> Result<(), Box<dyn StdError>> {
     let args = args::Args::parse();
@@ -41,18 +57,107 @@ fn main() -> Result<(), Box<dyn StdError>> {
     // Allocate 100KiB to start, these files are usually pretty large
     let mut s = String::with_capacity(100 * 1024);
 
-    BufReader::new(std::io::stdin()).read_to_string(&mut s)?;
+    let input = match &args.command {
+        args::Command::Generate {
+            input: Some(x),
+        } => Some(x),
+        _ => args.input.as_ref(),
+    };
+
+    let name = if let Some(path) = input {
+        File::open(path)?.read_to_string(&mut s)?;
+        path.display().to_string()
+    } else {
+        BufReader::new(std::io::stdin()).read_to_string(&mut s)?;
+        "<stdin>".to_owned()
+    };
+
+    // References to other files are resolved relative to the input, or to the
+    // working directory when reading from stdin
+    let path = input.map_or_else(|| Path::new(&name), PathBuf::as_path);
+
+    let filter = args.filter();
+    let options = codegen::Options {
+        tree_shake: args.tree_shake,
+        pagination: args.pagination(),
+    };
+
+    let mut diagnostics = parse::Diagnostics::default();
+    let d = &mut diagnostics;
 
-    let code = match args {
-        args::Args::Yaml => entrypoint::from_yaml(&s)?,
-        args::Args::Json => entrypoint::from_json(&s)?,
+    // `None` means the format has to be detected from the contents
+    let format = match &args.command {
+        args::Command::Yaml => Some(Format::Yaml),
+        args::Command::Json => Some(Format::Json),
+        args::Command::Generate {
+            ..
+        } => input.and_then(|x| Format::from_extension(x)),
     };
 
-    print!("{code}");
+    let output = match (format, args.package) {
+        (Some(Format::Yaml), false) => Output::Module(entrypoint::from_yaml(
+            &s, path, &filter, &options, d,
+        )?),
+        (Some(Format::Json), false) => Output::Module(entrypoint::from_json(
+            &s, path, &filter, &options, d,
+        )?),
+        (None, false) => Output::Module(entrypoint::from_str(
+            &s, path, &filter, &options, d,
+        )?),
+        (Some(Format::Yaml), true) => Output::Package(
+            entrypoint::package_from_yaml(&s, path, &filter, &options, d)?,
+        ),
+        (Some(Format::Json), true) => Output::Package(
+            entrypoint::package_from_json(&s, path, &filter, &options, d)?,
+        ),
+        (None, true) => Output::Package(entrypoint::package_from_str(
+            &s, path, &filter, &options, d,
+        )?),
+    };
+
+    eprint!("{}", diagnostics.render(&name, &s));
+
+    if args.strict {
+        let disallowed = diagnostics
+            .disallowed(&args.allow)
+            .map(parse::Error::pointer)
+            .collect::<Vec<_>>();
+
+        if !disallowed.is_empty() {
+            eprintln!("error: strict mode forbids skipping the following:");
+
+            for pointer in disallowed {
+                eprintln!("  {pointer}");
+            }
+
+            std::process::exit(1);
+        }
+    }
+
+    match (output, &args.output) {
+        (Output::Module(code), Some(path)) => std::fs::write(path, code)?,
+        (Output::Module(code), None) => {
+            print!("{code}");
+
+            // TTYs are line buffered, so we have to manually flush after
+            // potentially not ending with a newline
+            std::io::stdout().flush()?;
+        }
+        (Output::Package(files), Some(dir)) => {
+            for (path, contents) in files {
+                let path = dir.join(path);
+
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+
+                std::fs::write(path, contents)?;
+            }
+        }
 
-    // TTYs are line buffered, so we have to manually flush after potentially
-    // not ending with a newline
-    std::io::stdout().flush()?;
+        // Clap ensures an output directory is given in package mode
+        (Output::Package(_), None) => unreachable!(),
+    }
 
     Ok(())
 }
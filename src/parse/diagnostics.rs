@@ -0,0 +1,212 @@
+//! Collection of problems found while processing a document
+
+use super::{Error, SourceMap};
+
+/// How serious a problem is
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    /// Something was not quite right, but nothing was left out
+    Warning,
+
+    /// Something was left out of the generated code
+    Error,
+
+    /// Something was deliberately left out of the generated code
+    Note,
+}
+
+impl Severity {
+    /// The label this severity is printed with
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Note => "note",
+        }
+    }
+}
+
+/// Problems that were skipped over instead of aborting the whole run
+///
+/// Anything that can't be converted is recorded here and left out of the
+/// generated code, so that one odd schema doesn't prevent the rest of the
+/// document from being used.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    /// The problems encountered so far, in the order they were found
+    problems: Vec<(Severity, Error)>,
+}
+
+impl Diagnostics {
+    /// Record an error
+    pub fn push(&mut self, error: Error) {
+        self.problems.push((Severity::Error, error));
+    }
+
+    /// Record a warning
+    pub fn warn(&mut self, error: Error) {
+        self.problems.push((Severity::Warning, error));
+    }
+
+    /// Record something that was left out on purpose
+    pub fn note(&mut self, error: Error) {
+        self.problems.push((Severity::Note, error));
+    }
+
+    /// Problems that aren't covered by any of the `allow`ed JSON pointers
+    ///
+    /// A problem is covered if its location is an allowed pointer or anything
+    /// beneath one. Notes are never problems.
+    pub fn disallowed<'a>(
+        &'a self,
+        allow: &'a [String],
+    ) -> impl Iterator<Item = &'a Error> {
+        self.problems
+            .iter()
+            .filter(|(x, _)| *x != Severity::Note)
+            .map(|(_, x)| x)
+            .filter(move |x| {
+                let pointer = x.pointer();
+
+                !allow.iter().any(|allowed| {
+                    let allowed = allowed.trim_end_matches('/');
+
+                    pointer.strip_prefix(allowed).is_some_and(|rest| {
+                        rest.is_empty() || rest.starts_with('/')
+                    })
+                })
+            })
+    }
+
+    /// Format every problem in a compiler-like style
+    ///
+    /// `name` is how the document is referred to, and `text` is its original
+    /// contents, used to show line numbers and the offending line.
+    pub fn render(&self, name: &str, text: &str) -> String {
+        let source_map = SourceMap::new(text).unwrap_or_default();
+        let lines: Vec<&str> = text.lines().collect();
+
+        // Backtraces are only useful for debugging this tool itself, so follow
+        // the usual Rust convention for when to show them
+        let backtraces =
+            std::env::var_os("RUST_BACKTRACE").is_some_and(|x| x != "0");
+
+        let mut report = String::new();
+
+        for (severity, error) in &self.problems {
+            report.push_str(&format!(
+                "{}: {}\n",
+                severity.as_str(),
+                error.kind
+            ));
+
+            match source_map.position(&error.location) {
+                Some(position) => {
+                    let line_number = position.line.to_string();
+                    let gutter = " ".repeat(line_number.len());
+                    let line = position
+                        .line
+                        .checked_sub(1)
+                        .and_then(|x| lines.get(x))
+                        .unwrap_or(&"");
+                    let (line, offset) = excerpt(line, position.column);
+
+                    report.push_str(&format!(
+                        "{gutter}--> {name}:{}:{}\n{gutter} |\n{line_number} \
+                         | {line}\n{gutter} | {}^\n{gutter} = at {}\n",
+                        position.line,
+                        position.column,
+                        " ".repeat(offset),
+                        error.pointer(),
+                    ));
+                }
+                None => {
+                    report.push_str(&format!(
+                        " --> {name}\n  = at {}\n",
+                        error.pointer()
+                    ));
+                }
+            }
+
+            if backtraces {
+                report.push_str(&format!("{:?}\n", error.backtrace));
+            }
+
+            report.push('\n');
+        }
+
+        let count = |severity| {
+            self.problems.iter().filter(|(x, _)| *x == severity).count()
+        };
+
+        let (errors, warnings) =
+            (count(Severity::Error), count(Severity::Warning));
+
+        if errors + warnings > 0 {
+            report.push_str(&format!(
+                "{errors} error(s) and {warnings} warning(s) found, items \
+                 with errors were skipped\n",
+            ));
+        }
+
+        report
+    }
+}
+
+/// How many characters are shown on each side of a problem's column
+const CONTEXT: usize = 40;
+
+/// Cut a line down to the characters around a column, starting from 1
+///
+/// Minified documents can be a single line, which shouldn't be shown whole
+/// for every problem. Returns the excerpt and how many characters into it
+/// the column is.
+fn excerpt(line: &str, column: usize) -> (String, usize) {
+    let index = column.saturating_sub(1);
+    let length = line.chars().count();
+    let start = index.saturating_sub(CONTEXT);
+    let end = index.saturating_add(CONTEXT).min(length);
+
+    let mut excerpt: String =
+        line.chars().skip(start).take(end.saturating_sub(start)).collect();
+    let mut offset = index - start;
+
+    if start > 0 {
+        excerpt.insert_str(0, "...");
+        offset += 3;
+    }
+
+    if end < length {
+        excerpt.push_str("...");
+    }
+
+    (excerpt, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{excerpt, CONTEXT};
+
+    /// Short lines are shown whole
+    #[test]
+    fn short_lines() {
+        assert_eq!(excerpt("  type: foo", 3), ("  type: foo".to_owned(), 2));
+    }
+
+    /// Long lines are cut down on both sides of the column
+    #[test]
+    fn long_lines() {
+        let line = format!("{}é{}", "a".repeat(100), "b".repeat(100));
+        let (excerpt, offset) = excerpt(&line, 101);
+
+        assert_eq!(
+            excerpt,
+            format!(
+                "...{}é{}...",
+                "a".repeat(CONTEXT),
+                "b".repeat(CONTEXT - 1)
+            )
+        );
+        assert_eq!(excerpt.chars().nth(offset), Some('é'));
+    }
+}
@@ -14,6 +14,61 @@ pub struct Parse {
 
     /// The actual error information
     pub kind: ParseKind,
+
+    /// JSON pointer segments locating the offending node in the document
+    ///
+    /// These are unescaped; use [`Parse::pointer`](Parse::pointer) to get a
+    /// printable JSON pointer.
+    pub location: Vec<String>,
+}
+
+impl Parse {
+    /// Create an error for an unsupported keyword, located at that keyword
+    pub fn unsupported<S: Into<String>>(keyword: S) -> Self {
+        let keyword = keyword.into();
+
+        Self::from(ParseKind::UnsupportedKeyword(keyword.clone()))
+            .within([keyword])
+    }
+
+    /// Prefix the location of this error with more JSON pointer segments
+    ///
+    /// Errors are created deep inside the document, so callers add the
+    /// segments they know about as the error propagates back up.
+    #[must_use]
+    pub fn within<I, S>(mut self, segments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut location: Vec<String> =
+            segments.into_iter().map(Into::into).collect();
+
+        location.append(&mut self.location);
+        self.location = location;
+
+        self
+    }
+
+    /// Whether this error only means that a parser doesn't handle this kind of
+    /// schema, as opposed to a problem with the schema itself
+    pub fn is_mismatch(&self) -> bool {
+        self.location.is_empty()
+            && matches!(
+                self.kind,
+                ParseKind::Unimplemented | ParseKind::OtherType
+            )
+    }
+
+    /// The location of this error as a JSON pointer, such as
+    /// `#/components/schemas/Call`
+    pub fn pointer(&self) -> String {
+        self.location.iter().fold(String::from("#"), |mut acc, x| {
+            acc.push('/');
+            acc.push_str(&x.replace('~', "~0").replace('/', "~1"));
+            acc
+        })
+    }
 }
 
 impl From<ParseKind> for Parse {
@@ -21,13 +76,14 @@ impl From<ParseKind> for Parse {
         Self {
             kind: other,
             backtrace: Backtrace::new(),
+            location: Vec::new(),
         }
     }
 }
 
 impl fmt::Display for Parse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.kind.fmt(f)
+        write!(f, "{}: {}", self.pointer(), self.kind)
     }
 }
 
@@ -50,4 +106,50 @@ pub enum ParseKind {
     /// something that turns out to be an integer.
     #[error("this object is unsupported by this function")]
     OtherType,
+
+    /// A path template contains unbalanced or empty braces
+    #[error("malformed path template: {0}")]
+    MalformedPath(String),
+
+    /// The schema uses a keyword, or a form of a keyword, that isn't supported
+    #[error("unsupported keyword `{0}`")]
+    UnsupportedKeyword(String),
+
+    /// A parameter is passed somewhere that isn't supported, like a header
+    #[error("parameters in `{0}` are not sent")]
+    UnsupportedParameterLocation(String),
+
+    /// None of the media types of some content are supported
+    #[error("unsupported media type(s): {0}")]
+    UnsupportedMediaType(String),
+
+    /// A security scheme can't be used for authentication
+    #[error("unsupported security scheme")]
+    UnsupportedSecurityScheme,
+
+    /// The schema `false` was used, which no value matches
+    #[error("the schema `false` doesn't match any value")]
+    BooleanSchema,
+
+    /// A `$ref` doesn't point anywhere this tool can resolve
+    #[error("invalid reference: {0}")]
+    InvalidReference(String),
+
+    /// A path template references a parameter that was never declared
+    #[error(
+        "path template placeholder `{{{0}}}` has no matching path parameter"
+    )]
+    MissingPathParameter(String),
+
+    /// An operation's pagination can't be understood
+    #[error("invalid pagination: {0}")]
+    InvalidPagination(String),
+
+    /// The document's version can't be used as a Python package version
+    #[error("the version `{0}` isn't valid for a Python package, using 0.0.0")]
+    InvalidVersion(String),
+
+    /// A schema isn't used by any operation, so it was left out
+    #[error("the schema isn't used by any operation")]
+    UnusedSchema,
 }
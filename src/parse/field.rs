@@ -1,8 +1,9 @@
 //! Struct field parsing
 
 use okapi::{openapi3::SchemaObject, schemars::schema::Schema};
+use serde_json::Value;
 
-use super::{Error, Type};
+use super::{r#type::docs, Error, Type};
 
 /// A parsed field
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -10,11 +11,36 @@ pub struct Field {
     /// Type of the field
     pub r#type: Type,
 
+    /// Whether the field must be present
+    ///
+    /// Required fields may still have a [`Type::Option`](Type::Option) type if
+    /// `null` is an acceptable value.
+    pub required: bool,
+
     /// Field documentation
     pub docs: Option<String>,
 
     /// Whether the use of this field is deprecated
     pub deprecated: bool,
+
+    /// Which direction this field is sent in
+    pub access: Access,
+
+    /// The value the field has if it isn't present
+    pub default: Option<Value>,
+}
+
+/// Which direction a field is sent in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Access {
+    /// Sent by both the client and the server
+    ReadWrite,
+
+    /// Only ever sent by the server, like an ID assigned on creation
+    ReadOnly,
+
+    /// Only ever sent by the client, like a password
+    WriteOnly,
 }
 
 impl Field {
@@ -22,8 +48,11 @@ impl Field {
     pub fn new(r#type: Type) -> Self {
         Self {
             r#type,
+            required: true,
             docs: None,
             deprecated: false,
+            access: Access::ReadWrite,
+            default: None,
         }
     }
 
@@ -38,7 +67,7 @@ impl TryFrom<&Schema> for Field {
 
     fn try_from(schema: &Schema) -> Result<Self, Self::Error> {
         match schema {
-            Schema::Bool(_) => todo!(),
+            Schema::Bool(_) => Type::try_from(schema).map(Self::new),
             Schema::Object(x) => x.try_into(),
         }
     }
@@ -50,15 +79,24 @@ impl TryFrom<&SchemaObject> for Field {
     fn try_from(schema_object: &SchemaObject) -> Result<Self, Self::Error> {
         let mut x = Field::new(Type::try_from(schema_object)?);
 
-        if let Some(docs) =
-            schema_object.metadata.as_ref().and_then(|x| x.description.as_ref())
-        {
-            // TODO: include more things like examples, the title, and so on
+        if let Some(docs) = docs(schema_object) {
             x.set_docs(docs);
         }
 
-        if schema_object.metadata.as_ref().map_or(false, |x| x.deprecated) {
-            x.r#type = Type::Option(Box::new(x.r#type));
+        if let Some(metadata) = schema_object.metadata.as_deref() {
+            // A `null` default is the same as no default, so the field stays
+            // optional
+            x.default = metadata.default.clone().filter(|x| !x.is_null());
+            x.access = match (metadata.read_only, metadata.write_only) {
+                (true, false) => Access::ReadOnly,
+                (false, true) => Access::WriteOnly,
+                _ => Access::ReadWrite,
+            };
+        }
+
+        if schema_object.metadata.as_ref().is_some_and(|x| x.deprecated) {
+            x.r#type = x.r#type.optional();
+            x.required = false;
             x.deprecated = true;
 
             if let Some(x) = x.docs.as_mut() {
@@ -4,12 +4,17 @@ use std::collections::BTreeMap;
 
 use okapi::{
     openapi3::{
-        Operation, Parameter, ParameterValue, PathItem, RefOr, RequestBody,
+        MediaType, OpenApi, Operation, Parameter, ParameterStyle,
+        ParameterValue, RefOr, RequestBody, Responses,
     },
     schemars::Map,
 };
+use serde_json::Value;
 
-use super::{Error, ErrorKind, Type};
+use super::{
+    path_template, r#type::see_also, Diagnostics, Error, ErrorKind, Field,
+    Pagination, PathSegment, Struct, Type,
+};
 
 /// A parsed function
 #[derive(Debug)]
@@ -25,6 +30,30 @@ pub struct Function {
 
     /// The responses returned by this API request
     pub responses: BTreeMap<String, Type>,
+
+    /// Descriptions of every response this API request can return, including
+    /// ones without a body, keyed by status code
+    pub response_docs: BTreeMap<String, String>,
+
+    /// The headers the responses to this API request declare
+    ///
+    /// Header names are case-insensitive, so these are keyed by their
+    /// lowercase names. Headers are only required if every response requires
+    /// them.
+    pub response_headers: Option<Struct>,
+
+    /// The HTTP path this request is sent to, split into its template pieces
+    pub path: Vec<PathSegment>,
+
+    /// Tags used to group this function with related ones
+    pub tags: Vec<String>,
+
+    /// Whether callers should stop using this function
+    pub deprecated: bool,
+
+    /// How to get every page of results, if this function returns them a page
+    /// at a time
+    pub pagination: Option<Pagination>,
 }
 
 /// An owned HTTP method
@@ -38,96 +67,496 @@ pub type HttpMethodBuf = String;
 pub type OpenApiPathBuf = String;
 
 /// Functions generated from OpenAPI
-type Functions = BTreeMap<(HttpMethodBuf, OpenApiPathBuf), Function>;
+pub type Functions = BTreeMap<(HttpMethodBuf, OpenApiPathBuf), Function>;
 
 /// Deduplicates HTTP method specification
 macro_rules! parse_function {
-    ($functions:ident, $path:ident, $path_item:ident, $method:ident) => {
+    (
+        $functions:ident,
+        $diagnostics:ident,
+        $parameters:ident,
+        $path:ident,
+        $path_item:ident,
+        $method:ident
+    ) => {
         if let Some(operation) = $path_item.$method.as_ref() {
-            eprintln!("{}\t{}", stringify!($method), $path);
-            $functions.insert(
-                (stringify!($method).to_owned(), $path.to_owned()),
-                Self::try_from_operation(operation)?,
+            let mut skipped = Vec::new();
+
+            let function = Self::try_from_operation(
+                $path,
+                stringify!($method),
+                $parameters,
+                &$path_item.parameters,
+                operation,
+                &mut skipped,
             );
+
+            for e in skipped {
+                $diagnostics.warn(e.within(["paths", $path]));
+            }
+
+            match function {
+                Ok(function) => {
+                    $functions.insert(
+                        (stringify!($method).to_owned(), $path.to_owned()),
+                        function,
+                    );
+                }
+                Err(e) => $diagnostics.push(e.within(["paths", $path])),
+            }
         }
     };
 }
 
 impl Function {
     /// Generate a map of HTTP paths to function signatures from OpenAPI data
+    ///
+    /// Operations that can't be parsed are left out and recorded in
+    /// `diagnostics`.
     pub fn try_from_paths(
-        paths: &Map<OpenApiPathBuf, PathItem>,
-    ) -> Result<Functions, Error> {
-        paths.iter().try_fold(Functions::default(), |mut acc, (path, info)| {
-            parse_function!(acc, path, info, get);
-            parse_function!(acc, path, info, put);
-            parse_function!(acc, path, info, post);
-            parse_function!(acc, path, info, delete);
-            parse_function!(acc, path, info, options);
-            parse_function!(acc, path, info, head);
-            parse_function!(acc, path, info, patch);
-            parse_function!(acc, path, info, trace);
-
-            Ok(acc)
-        })
+        openapi: &OpenApi,
+        diagnostics: &mut Diagnostics,
+    ) -> Functions {
+        let mut fs = Functions::default();
+
+        let empty = Map::new();
+        let parameters =
+            openapi.components.as_ref().map_or(&empty, |x| &x.parameters);
+
+        for (path, info) in &openapi.paths {
+            parse_function!(fs, diagnostics, parameters, path, info, get);
+            parse_function!(fs, diagnostics, parameters, path, info, put);
+            parse_function!(fs, diagnostics, parameters, path, info, post);
+            parse_function!(fs, diagnostics, parameters, path, info, delete);
+            parse_function!(fs, diagnostics, parameters, path, info, options);
+            parse_function!(fs, diagnostics, parameters, path, info, head);
+            parse_function!(fs, diagnostics, parameters, path, info, patch);
+            parse_function!(fs, diagnostics, parameters, path, info, trace);
+        }
+
+        fs
+    }
+
+    /// The only successful response with a JSON body, if there's exactly one
+    pub fn success_response(&self) -> Option<(&String, &Type)> {
+        success_response(&self.responses)
     }
 
     /// Generates a method for a given HTTP URL and HTTP method
-    fn try_from_operation(operation: &Operation) -> Result<Self, Error> {
+    ///
+    /// `path_parameters` are the parameters shared by every operation on the
+    /// path this operation belongs to, and `components` are the parameters
+    /// they can refer to. Errors are located relative to the
+    /// path item. Parts of the operation that have to be left out are
+    /// recorded in `skipped`, also relative to the path item.
+    fn try_from_operation(
+        path: &str,
+        method: &str,
+        components: &Map<String, RefOr<Parameter>>,
+        path_parameters: &[RefOr<Parameter>],
+        operation: &Operation,
+        skipped: &mut Vec<Error>,
+    ) -> Result<Self, Error> {
+        let parameters = merge_parameters(
+            method,
+            components,
+            path_parameters,
+            &operation.parameters,
+            skipped,
+        );
         let mut arguments =
-            Argument::try_from_parameters(operation.parameters.iter())?;
-
-        arguments.extend(Argument::try_from_request_body(
-            operation.request_body.iter(),
-        )?);
-
-        let responses = operation
-            .responses
-            .responses
-            .iter()
-            .map(|(code, response)| match response {
-                RefOr::Object(x) => Ok((code, x)),
-                RefOr::Ref(_) => Err(Error::from(ErrorKind::Unimplemented)),
-            })
-            .filter_map(|x| {
-                x.map(|(code, x)| {
-                    x.content.get("application/json").map(|x| (code, x))
-                })
-                .transpose()
-            })
-            .map(|x| {
-                x.and_then(|(code, x)| {
-                    x.schema.as_ref().map_or_else(
-                        // Work around incomplete specs by assuming
-                        // correctly-set content type but missing SchemaObject
-                        // means it's any JSON type.
-                        || Ok((code, Type::Any)),
-                        |x| Ok((code, Type::try_from(x)?)),
-                    )
-                })
-            })
-            .try_fold(BTreeMap::new(), |mut acc, x| {
-                let (code, response) = x?;
-                acc.insert(code.clone(), response);
-                Ok::<_, Error>(acc)
-            })?;
+            Argument::try_from_parameters(parameters.into_iter(), skipped)?;
+
+        let mut body_skipped = Vec::new();
+
+        arguments.extend(
+            Argument::try_from_request_body(
+                operation.request_body.iter(),
+                &mut body_skipped,
+            )
+            .map_err(|e| e.within([method, "requestBody"]))?,
+        );
+
+        skipped.extend(
+            body_skipped.into_iter().map(|e| e.within([method, "requestBody"])),
+        );
+
+        let path = path_template(path)?;
+
+        // Every placeholder must be filled in by an argument, otherwise the
+        // generated code would reference an undefined variable
+        for segment in &path {
+            if let PathSegment::Parameter(name) = segment {
+                let declared = arguments
+                    .iter()
+                    .any(|x| x.location == Location::Path && &x.name == name);
+
+                if !declared {
+                    return Err(
+                        ErrorKind::MissingPathParameter(name.clone()).into()
+                    );
+                }
+            }
+        }
+
+        let responses = responses(method, &operation.responses, skipped)?;
+
+        let pagination =
+            pagination(method, operation, &arguments, &responses, skipped);
 
         Ok(Function {
+            pagination,
             arguments,
             responses,
-
-            // TODO: include more things like examples, summary, and so on
-            docs: operation.description.clone(),
+            response_docs: response_docs(&operation.responses),
+            response_headers: response_headers(
+                method,
+                &operation.responses,
+                skipped,
+            ),
+            path,
+            tags: operation.tags.clone(),
+            docs: operation_docs(operation),
+            deprecated: operation.deprecated,
             security_schemes: operation
                 .security
                 .iter()
-                .flat_map(|x| x.iter().flat_map(|x| x.iter().map(|(k, _)| k)))
+                .flat_map(|x| x.iter().flat_map(|x| x.keys()))
                 .cloned()
                 .collect(),
         })
     }
 }
 
+/// Parse the JSON body of every response, keyed by status code
+///
+/// Responses without a JSON body are left out, and ones with only other media
+/// types are recorded in `skipped`, relative to the path item.
+fn responses(
+    method: &str,
+    responses: &Responses,
+    skipped: &mut Vec<Error>,
+) -> Result<BTreeMap<String, Type>, Error> {
+    responses
+        .responses
+        .iter()
+        .map(|(code, response)| match response {
+            RefOr::Object(x) => Ok((code, x)),
+            RefOr::Ref(_) => Err(Error::unsupported("$ref").within([
+                method,
+                "responses",
+                code,
+            ])),
+        })
+        .filter_map(|x| {
+            x.map(|(code, x)| {
+                if !x.content.is_empty()
+                    && !x.content.contains_key("application/json")
+                {
+                    skipped.push(unsupported_media_types(&x.content).within([
+                        method,
+                        "responses",
+                        code,
+                    ]));
+                }
+
+                x.content.get("application/json").map(|x| (code, x))
+            })
+            .transpose()
+        })
+        .map(|x| {
+            x.and_then(|(code, x)| {
+                x.schema.as_ref().map_or_else(
+                    // Work around incomplete specs by assuming
+                    // correctly-set content type but missing SchemaObject
+                    // means it's any JSON type.
+                    || Ok((code, Type::Any)),
+                    |x| {
+                        let ty = Type::try_from(x).map_err(|e| {
+                            e.within([
+                                method,
+                                "responses",
+                                code,
+                                "content",
+                                "application/json",
+                                "schema",
+                            ])
+                        })?;
+
+                        Ok((code, ty))
+                    },
+                )
+            })
+        })
+        .try_fold(BTreeMap::new(), |mut acc, x| {
+            let (code, response) = x?;
+            acc.insert(code.clone(), response);
+            Ok::<_, Error>(acc)
+        })
+}
+
+/// Find the only response with a `2xx` status, if there's exactly one
+///
+/// Error responses often have bodies too, so they don't count.
+fn success_response(
+    responses: &BTreeMap<String, Type>,
+) -> Option<(&String, &Type)> {
+    let mut successes = responses.iter().filter(|(x, _)| x.starts_with('2'));
+
+    match (successes.next(), successes.next()) {
+        (Some(x), None) => Some(x),
+        _ => None,
+    }
+}
+
+/// Parse an operation's `x-pagination` extension, if it has one
+///
+/// Pages are read from the operation's only successful response. Invalid
+/// pagination is recorded in `skipped`, relative to the path item.
+fn pagination(
+    method: &str,
+    operation: &Operation,
+    arguments: &[Argument],
+    responses: &BTreeMap<String, Type>,
+    skipped: &mut Vec<Error>,
+) -> Option<Pagination> {
+    let extension = operation.extensions.get("x-pagination")?;
+
+    let pagination = match Pagination::try_from_extension(extension) {
+        Ok(x) => x,
+        Err(e) => {
+            skipped.push(e.within([method]));
+            return None;
+        }
+    };
+
+    let has_parameter = arguments.iter().any(|x| {
+        x.location == Location::Query && x.name == pagination.parameter
+    });
+
+    if !has_parameter {
+        skipped.push(
+            Error::from(ErrorKind::InvalidPagination(format!(
+                "there is no `{}` query parameter",
+                pagination.parameter
+            )))
+            .within([method, "x-pagination", "parameter"]),
+        );
+
+        return None;
+    }
+
+    if success_response(responses).is_none() {
+        skipped.push(
+            Error::from(ErrorKind::InvalidPagination(
+                "there isn't exactly one successful response with a JSON body"
+                    .to_owned(),
+            ))
+            .within([method, "x-pagination"]),
+        );
+
+        return None;
+    }
+
+    Some(pagination)
+}
+
+/// Combine an operation's summary, description, and external documentation
+fn operation_docs(operation: &Operation) -> Option<String> {
+    let see_also = operation
+        .external_docs
+        .as_ref()
+        .map(|x| see_also(&x.url, x.description.as_deref()));
+
+    let paragraphs: Vec<&str> = [
+        operation.summary.as_deref(),
+        operation.description.as_deref(),
+        see_also.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|x| !x.is_empty())
+    .collect();
+
+    (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n"))
+}
+
+/// Collect the description of every response, keyed by status code
+fn response_docs(responses: &Responses) -> BTreeMap<String, String> {
+    responses
+        .responses
+        .iter()
+        .map(|(code, x)| (code.as_str(), x))
+        .chain(responses.default.iter().map(|x| ("default", x)))
+        .filter_map(|(code, x)| match x {
+            RefOr::Object(x) if !x.description.trim().is_empty() => {
+                Some((code.to_owned(), x.description.trim().to_owned()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collect the headers declared by every response into one struct
+///
+/// `Content-Type` is left out, as OpenAPI says to ignore it. Headers that
+/// can't be represented are recorded in `skipped`, relative to the path item.
+fn response_headers(
+    method: &str,
+    responses: &Responses,
+    skipped: &mut Vec<Error>,
+) -> Option<Struct> {
+    let responses: Vec<_> = responses
+        .responses
+        .iter()
+        .map(|(code, x)| (code.as_str(), x))
+        .chain(responses.default.iter().map(|x| ("default", x)))
+        .filter_map(|(code, x)| match x {
+            RefOr::Object(x) => Some((code, x)),
+            RefOr::Ref(_) => None,
+        })
+        .collect();
+
+    let mut fields = BTreeMap::new();
+
+    for (code, response) in &responses {
+        for (name, header) in &response.headers {
+            let key = name.to_ascii_lowercase();
+
+            if key == "content-type" || fields.contains_key(&key) {
+                continue;
+            }
+
+            let pointer = [method, "responses", code, "headers", name.as_str()];
+
+            let RefOr::Object(header) = header else {
+                skipped.push(Error::unsupported("$ref").within(pointer));
+                continue;
+            };
+
+            let ParameterValue::Schema {
+                schema,
+                ..
+            } = &header.value
+            else {
+                skipped.push(Error::unsupported("content").within(pointer));
+                continue;
+            };
+
+            let r#type = match Type::try_from(schema) {
+                Ok(x) => x,
+                Err(e) => {
+                    skipped.push(e.within(["schema"]).within(pointer));
+                    continue;
+                }
+            };
+
+            // Responses that don't declare a header don't send it
+            let required = responses.iter().all(|(_, x)| {
+                x.headers.iter().any(|(name, x)| {
+                    name.to_ascii_lowercase() == key
+                        && matches!(x, RefOr::Object(x) if x.required)
+                })
+            });
+
+            let mut field = Field::new(if required {
+                r#type
+            } else {
+                r#type.optional()
+            });
+            field.required = required;
+            field.deprecated = header.deprecated;
+            field.docs.clone_from(&header.description);
+
+            fields.insert(key, field);
+        }
+    }
+
+    (!fields.is_empty()).then(|| Struct::from(fields))
+}
+
+/// Describe content that was skipped because none of it is JSON
+fn unsupported_media_types(content: &Map<String, MediaType>) -> Error {
+    let media_types = content.keys().cloned().collect::<Vec<_>>().join(", ");
+
+    Error::from(ErrorKind::UnsupportedMediaType(media_types))
+        .within(["content"])
+}
+
+/// Combine path-level parameters with operation-level parameters
+///
+/// References to `components` are resolved first. Operation-level parameters
+/// then override path-level parameters that share both their name and
+/// location. Each parameter is paired with its location relative to the path
+/// item, and ones that can't be resolved are recorded in `skipped`.
+fn merge_parameters<'a>(
+    method: &str,
+    components: &'a Map<String, RefOr<Parameter>>,
+    path_parameters: &'a [RefOr<Parameter>],
+    operation_parameters: &'a [RefOr<Parameter>],
+    skipped: &mut Vec<Error>,
+) -> Vec<(Vec<String>, &'a Parameter)> {
+    let mut resolve = |prefix: &[&str], parameters: &'a [RefOr<Parameter>]| {
+        let mut resolved = Vec::new();
+
+        for (i, x) in parameters.iter().enumerate() {
+            let pointer: Vec<String> = prefix
+                .iter()
+                .map(|x| (*x).to_owned())
+                .chain([i.to_string()])
+                .collect();
+
+            match resolve_parameter(components, x) {
+                Ok(x) => resolved.push((pointer, x)),
+                Err(e) => skipped.push(e.within(pointer)),
+            }
+        }
+
+        resolved
+    };
+
+    let path_parameters = resolve(&["parameters"], path_parameters);
+    let operation_parameters =
+        resolve(&[method, "parameters"], operation_parameters);
+
+    let overridden = |path_param: &Parameter| {
+        operation_parameters.iter().any(|(_, x)| {
+            x.name == path_param.name && x.location == path_param.location
+        })
+    };
+
+    path_parameters
+        .into_iter()
+        .filter(|(_, x)| !overridden(x))
+        .chain(operation_parameters.iter().cloned())
+        .collect()
+}
+
+/// Follow a parameter's references to `#/components/parameters`
+fn resolve_parameter<'a>(
+    components: &'a Map<String, RefOr<Parameter>>,
+    mut parameter: &'a RefOr<Parameter>,
+) -> Result<&'a Parameter, Error> {
+    // Following more references than there are components means there's a
+    // cycle
+    for _ in 0..=components.len() {
+        let reference = match parameter {
+            RefOr::Object(x) => return Ok(x),
+            RefOr::Ref(x) => &x.reference,
+        };
+
+        parameter = reference
+            .strip_prefix("#/components/parameters/")
+            .map(|x| x.replace("~1", "/").replace("~0", "~"))
+            .and_then(|x| components.get(&x))
+            .ok_or_else(|| ErrorKind::InvalidReference(reference.clone()))?;
+    }
+
+    Err(ErrorKind::InvalidReference("cyclic parameter reference".to_owned())
+        .into())
+}
+
 /// Where an argument is passed to the HTTP request
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Location {
@@ -137,11 +566,69 @@ pub enum Location {
     /// This argument goes in the path parameters
     Path,
 
+    /// This argument goes in the request headers
+    Header,
+
     /// This argument goes in the request body
     Body,
+}
+
+/// How an argument is serialized into the path or query string
+///
+/// See the [OpenAPI documentation][style] for examples of each style.
+///
+/// [style]: https://github.com/OAI/OpenAPI-Specification/blob/main/versions/3.0.3.md#style-values
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    /// `;name=value` path parameters
+    Matrix,
+
+    /// `.value` path parameters
+    Label,
 
-    /// This argument goes... somewhere, probably
-    Unimplemented,
+    /// `name=value` query parameters, the default for queries
+    Form,
+
+    /// Comma-separated values, the default for paths
+    Simple,
+
+    /// Space-separated list query parameters
+    SpaceDelimited,
+
+    /// Pipe-separated list query parameters
+    PipeDelimited,
+
+    /// `name[key]=value` object query parameters
+    DeepObject,
+}
+
+impl Style {
+    /// The name of this style as written in an OpenAPI document
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Matrix => "matrix",
+            Self::Label => "label",
+            Self::Form => "form",
+            Self::Simple => "simple",
+            Self::SpaceDelimited => "spaceDelimited",
+            Self::PipeDelimited => "pipeDelimited",
+            Self::DeepObject => "deepObject",
+        }
+    }
+}
+
+impl From<&ParameterStyle> for Style {
+    fn from(x: &ParameterStyle) -> Self {
+        match x {
+            ParameterStyle::Matrix => Self::Matrix,
+            ParameterStyle::Label => Self::Label,
+            ParameterStyle::Form => Self::Form,
+            ParameterStyle::Simple => Self::Simple,
+            ParameterStyle::SpaceDelimited => Self::SpaceDelimited,
+            ParameterStyle::PipeDelimited => Self::PipeDelimited,
+            ParameterStyle::DeepObject => Self::DeepObject,
+        }
+    }
 }
 
 /// A parsed function argument
@@ -153,61 +640,134 @@ pub struct Argument {
     /// The type of this argument
     pub r#type: Type,
 
+    /// This argument's documentation
+    pub docs: Option<String>,
+
+    /// Whether this argument must be passed
+    ///
+    /// Required arguments may still have a [`Type::Option`](Type::Option)
+    /// type if `null` is an acceptable value.
+    pub required: bool,
+
+    /// The value the server uses if this argument isn't passed
+    pub default: Option<Value>,
+
+    /// Whether callers should stop passing this argument
+    pub deprecated: bool,
+
     /// Where this argument gets passed in the request
     pub location: Location,
+
+    /// How this argument is serialized
+    ///
+    /// This is only meaningful for path, query, and header arguments.
+    pub style: Style,
+
+    /// Whether lists and objects are split into separate parameters
+    ///
+    /// This is only meaningful for path, query, and header arguments.
+    pub explode: bool,
 }
 
 impl Argument {
     /// Construct a list of arguments out of HTTP path arguments
-    fn try_from_parameters<'a, I>(ref_or_params: I) -> Result<Vec<Self>, Error>
+    ///
+    /// Each parameter comes with its location in the document for error
+    /// reporting. Parameters that can't be represented are recorded in
+    /// `skipped`.
+    fn try_from_parameters<'a, I>(
+        params: I,
+        skipped: &mut Vec<Error>,
+    ) -> Result<Vec<Self>, Error>
     where
-        I: Iterator<Item = &'a RefOr<Parameter>>,
+        I: Iterator<Item = (Vec<String>, &'a Parameter)>,
     {
-        ref_or_params
-            .filter_map(|ref_or_param| {
-                if let RefOr::Object(param) = ref_or_param {
-                    Some(param)
-                } else {
-                    // TODO: is this lossy?
-                    None
-                }
-            })
-            .filter_map(|param| {
-                if let ParameterValue::Schema {
-                    schema,
-                    ..
-                } = &param.value
+        let mut arguments = Vec::new();
+
+        for (pointer, param) in params {
+            let ParameterValue::Schema {
+                schema,
+                style,
+                explode,
+                ..
+            } = &param.value
+            else {
+                skipped.push(Error::unsupported("content").within(pointer));
+                continue;
+            };
+
+            let location = match param.location.as_str() {
+                "path" => Location::Path,
+                "query" => Location::Query,
+
+                // These are set by the client, so OpenAPI says to ignore them
+                "header"
+                    if ["Accept", "Content-Type", "Authorization"]
+                        .iter()
+                        .any(|x| x.eq_ignore_ascii_case(&param.name)) =>
                 {
-                    let location = match param.location.as_str() {
-                        "path" => Location::Path,
-                        "query" => Location::Query,
-                        _ => Location::Unimplemented,
-                    };
+                    continue;
+                }
+                "header" => Location::Header,
+                x => {
+                    skipped.push(
+                        Error::from(ErrorKind::UnsupportedParameterLocation(
+                            x.to_owned(),
+                        ))
+                        .within(pointer),
+                    );
 
-                    Some((param, location, schema))
-                } else {
-                    // TODO: is this lossy?
-                    None
+                    continue;
                 }
-            })
-            .try_fold(Vec::default(), |mut acc, (param, location, schema)| {
-                acc.push(Argument {
-                    location,
-                    name: param.name.clone(),
-                    r#type: if param.required {
-                        schema.try_into()?
-                    } else {
-                        Type::Option(Box::new(schema.try_into()?))
-                    },
-                });
+            };
 
-                Ok::<_, Error>(acc)
-            })
+            // Defaults are defined by the OpenAPI spec
+            let style = style.as_ref().map_or_else(
+                || match location {
+                    Location::Query => Style::Form,
+                    _ => Style::Simple,
+                },
+                Style::from,
+            );
+            let explode = explode.unwrap_or(style == Style::Form);
+
+            let r#type = Type::try_from(schema).map_err(|e| {
+                e.within(pointer.into_iter().chain(["schema".to_owned()]))
+            })?;
+
+            // A `null` default is the same as no default
+            let default = schema
+                .metadata
+                .as_ref()
+                .and_then(|x| x.default.clone())
+                .filter(|x| !x.is_null());
+
+            arguments.push(Argument {
+                location,
+                style,
+                explode,
+                name: param.name.clone(),
+                docs: param.description.clone(),
+                required: param.required,
+                deprecated: param.deprecated,
+                r#type: if param.required || default.is_some() {
+                    r#type
+                } else {
+                    r#type.optional()
+                },
+                default,
+            });
+        }
+
+        Ok(arguments)
     }
 
     /// Potentially construct an argument from HTTP request body information
+    ///
+    /// Bodies that can't be represented are recorded in `skipped`.
     fn try_from_request_body<'a, I>(
         ref_or_body: I,
+        skipped: &mut Vec<Error>,
     ) -> Result<Option<Self>, Error>
     where
         I: Iterator<Item = &'a RefOr<RequestBody>>,
@@ -215,27 +775,52 @@ impl Argument {
         ref_or_body
             .map(|ref_or_body| match ref_or_body {
                 RefOr::Object(x) => Ok(x),
-                RefOr::Ref(_) => Err(Error::from(ErrorKind::Unimplemented)),
+                RefOr::Ref(_) => Err(Error::unsupported("$ref")),
             })
             .filter_map(|x| {
-                x.map(|x| x.content.get("application/json")).transpose()
+                x.map(|x| {
+                    if !x.content.is_empty()
+                        && !x.content.contains_key("application/json")
+                    {
+                        skipped.push(unsupported_media_types(&x.content));
+                    }
+
+                    x.content.get("application/json").map(|y| (x, y))
+                })
+                .transpose()
             })
             .map(|x| {
-                x.and_then(|x| {
-                    x.schema.as_ref().map_or_else(
+                x.and_then(|(body, x)| {
+                    let r#type = x.schema.as_ref().map_or_else(
                         // Work around incomplete specs by assuming
                         // correctly-set content type but missing SchemaObject
                         // means it's any JSON type.
                         || Ok(Type::Any),
-                        Type::try_from,
-                    )
+                        |x| {
+                            Type::try_from(x).map_err(|e| {
+                                e.within([
+                                    "content",
+                                    "application/json",
+                                    "schema",
+                                ])
+                            })
+                        },
+                    );
+
+                    r#type.map(|x| (body, x))
                 })
             })
             .map(|x| {
-                x.map(|x| Self {
+                x.map(|(body, x)| Self {
                     name: "body".to_owned(),
+                    docs: body.description.clone(),
                     r#type: x,
+                    required: true,
+                    default: None,
+                    deprecated: false,
                     location: Location::Body,
+                    style: Style::Simple,
+                    explode: false,
                 })
             })
             .next()
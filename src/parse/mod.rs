@@ -1,15 +1,23 @@
 //! Facilities for parsing OpenAPI schemas into meaningful structures
 
+mod diagnostics;
 mod error;
 mod field;
 mod function;
+mod pagination;
+mod path;
 mod security_schemes;
+mod source_map;
 mod r#struct;
 mod r#type;
 
+pub use diagnostics::Diagnostics;
 pub use error::{Parse as Error, ParseKind as ErrorKind};
-pub use field::Field;
-pub use function::{Argument, Function, Location};
+pub use field::{Access, Field};
+pub use function::{Argument, Function, Functions, Location, Style};
+pub use pagination::Pagination;
+pub use path::{path_template, PathSegment};
 pub use r#struct::Struct;
-pub use r#type::Type;
+pub use r#type::{docs, Type};
 pub use security_schemes::{security_schemes, SecurityScheme};
+pub use source_map::SourceMap;
@@ -0,0 +1,94 @@
+//! Pagination parsing
+
+use serde_json::Value;
+
+use super::{Error, ErrorKind, Type};
+
+/// How to get every page of results from an operation
+///
+/// Each response contains the value to pass as `parameter` to get the next
+/// page, until the last page, where that value is `null` or missing. This
+/// covers both page numbers and cursors.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pagination {
+    /// The query parameter that selects a page
+    pub parameter: String,
+
+    /// The field names leading from a response to the value of `parameter`
+    /// for the next page, such as `meta` then `next_page`
+    pub next: Vec<String>,
+
+    /// The field names leading from a response to the list of items on a page
+    pub items: Vec<String>,
+
+    /// The type of each item
+    ///
+    /// This is [`Type::Any`](Type::Any) until the item is looked up in the
+    /// document's schemas.
+    pub item: Type,
+}
+
+impl Pagination {
+    /// Create a pagination pattern out of `.`-separated field names
+    pub fn new(parameter: &str, next: &str, items: &str) -> Self {
+        Self {
+            parameter: parameter.to_owned(),
+            next: field_path(next),
+            items: field_path(items),
+            item: Type::Any,
+        }
+    }
+
+    /// Parse an `x-pagination` specification extension
+    ///
+    /// The extension is an object with `parameter`, `next`, and `items`
+    /// properties, like so:
+    ///
+    /// ```yaml
+    /// x-pagination:
+    ///   parameter: page[number]
+    ///   next: meta.next_page
+    ///   items: data
+    /// ```
+    ///
+    /// `next` and `items` are either `.`-separated field names or lists of
+    /// them.
+    pub fn try_from_extension(value: &Value) -> Result<Self, Error> {
+        let invalid = |x: &str| {
+            Error::from(ErrorKind::InvalidPagination(x.to_owned()))
+                .within(["x-pagination"])
+        };
+
+        let Some(parameter) = value.get("parameter").and_then(Value::as_str)
+        else {
+            return Err(invalid("`parameter` must be a string"));
+        };
+
+        let path = |key: &str| match value.get(key) {
+            Some(Value::String(x)) => Ok(field_path(x)),
+            Some(Value::Array(xs)) => xs
+                .iter()
+                .map(|x| x.as_str().map(ToOwned::to_owned))
+                .collect::<Option<Vec<_>>>()
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| {
+                    invalid(&format!("`{key}` must only contain strings"))
+                }),
+            _ => Err(invalid(&format!(
+                "`{key}` must be a string or a list of strings"
+            ))),
+        };
+
+        Ok(Self {
+            parameter: parameter.to_owned(),
+            next: path("next")?,
+            items: path("items")?,
+            item: Type::Any,
+        })
+    }
+}
+
+/// Split `.`-separated field names
+fn field_path(s: &str) -> Vec<String> {
+    s.split('.').map(ToOwned::to_owned).collect()
+}
@@ -0,0 +1,106 @@
+//! OpenAPI path template parsing
+
+use super::{Error, ErrorKind};
+
+/// A piece of an OpenAPI path template
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathSegment {
+    /// Text that is sent as-is
+    Literal(String),
+
+    /// The name of a path parameter whose value is substituted in
+    Parameter(String),
+}
+
+/// Split an OpenAPI path template into literal text and parameters
+///
+/// For example, `/foo/{bar}` becomes `[Literal("/foo/"), Parameter("bar")]`.
+pub fn path_template(path: &str) -> Result<Vec<PathSegment>, Error> {
+    let malformed = || Error::from(ErrorKind::MalformedPath(path.to_owned()));
+
+    let mut segments = Vec::new();
+    let mut rest = path;
+
+    while let Some((literal, tail)) = rest.split_once('{') {
+        let (name, tail) = tail.split_once('}').ok_or_else(malformed)?;
+
+        // Braces only ever come in pairs around a non-empty name
+        if literal.contains('}') || name.is_empty() || name.contains('{') {
+            return Err(malformed());
+        }
+
+        if !literal.is_empty() {
+            segments.push(PathSegment::Literal(literal.to_owned()));
+        }
+
+        segments.push(PathSegment::Parameter(name.to_owned()));
+
+        rest = tail;
+    }
+
+    if rest.contains('}') {
+        return Err(malformed());
+    }
+
+    if !rest.is_empty() {
+        segments.push(PathSegment::Literal(rest.to_owned()));
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{path_template, PathSegment};
+
+    /// Shorthand for a literal segment
+    fn literal(x: &str) -> PathSegment {
+        PathSegment::Literal(x.to_owned())
+    }
+
+    /// Shorthand for a parameter segment
+    fn parameter(x: &str) -> PathSegment {
+        PathSegment::Parameter(x.to_owned())
+    }
+
+    /// Literals and parameters are split apart
+    #[test]
+    fn splits_parameters() {
+        assert_eq!(
+            path_template("/a/{b}/c{d}").ok(),
+            Some(vec![
+                literal("/a/"),
+                parameter("b"),
+                literal("/c"),
+                parameter("d"),
+            ])
+        );
+    }
+
+    /// Paths without parameters are a single literal
+    #[test]
+    fn literal_only() {
+        assert_eq!(path_template("/a/b").ok(), Some(vec![literal("/a/b")]));
+        assert_eq!(path_template("").ok(), Some(vec![]));
+    }
+
+    /// Adjacent parameters don't need a literal between them
+    #[test]
+    fn adjacent_parameters() {
+        assert_eq!(
+            path_template("{a}{b}").ok(),
+            Some(vec![parameter("a"), parameter("b")])
+        );
+    }
+
+    /// Unbalanced, doubled, stray, or empty braces are rejected
+    #[test]
+    fn rejects_malformed() {
+        for path in [
+            "/a/{b}}", "/a/{{b}", "/a/{{b}}", "/a}/{b}", "/a}", "/a/{b",
+            "/a/{}", "/a/{b{c}",
+        ] {
+            assert!(path_template(path).is_err(), "{path} was accepted");
+        }
+    }
+}
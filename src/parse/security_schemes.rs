@@ -9,6 +9,8 @@ use okapi::{
     schemars::Map,
 };
 
+use super::{Diagnostics, Error, ErrorKind};
+
 /// HTTP authentication methods
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SecurityScheme {
@@ -17,37 +19,45 @@ pub enum SecurityScheme {
 }
 
 /// Parse OpenAPI security schemes
+///
+/// Unsupported schemes are left out and recorded in `diagnostics`.
 // It's only zero-sized for now
 #[allow(clippy::zero_sized_map_values)]
 pub fn security_schemes(
     security_schemes: &Map<String, RefOr<OkapiSecurityScheme>>,
+    diagnostics: &mut Diagnostics,
 ) -> BTreeMap<String, SecurityScheme> {
     let mut schemes = BTreeMap::default();
 
+    let mut unsupported = |name: &str| {
+        diagnostics.warn(
+            Error::from(ErrorKind::UnsupportedSecurityScheme).within([
+                "components",
+                "securitySchemes",
+                name,
+            ]),
+        );
+    };
+
     for (name, scheme) in security_schemes {
-        let object = if let RefOr::Object(x) = scheme {
-            x
-        } else {
-            eprintln!("unsupported security scheme: {name}");
+        let RefOr::Object(object) = scheme else {
+            unsupported(name);
             continue;
         };
 
-        let http = if let SecuritySchemeData::Http {
-            scheme,
+        let SecuritySchemeData::Http {
+            scheme: http,
             bearer_format: None,
         } = &object.data
-        {
-            scheme
-        } else {
-            eprintln!("unsupported security scheme: {name}");
+        else {
+            unsupported(name);
             continue;
         };
 
         if http.contains("basic") {
             schemes.insert(name.clone(), SecurityScheme::BasicAuth);
         } else {
-            eprintln!("unsupported security scheme: {name}");
-            continue;
+            unsupported(name);
         }
     }
 
@@ -0,0 +1,262 @@
+//! Mapping JSON pointers back to positions in the original document
+
+use std::collections::HashMap;
+
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+/// A position in a source document
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    /// The line number, starting from 1
+    pub line: usize,
+
+    /// The column number, starting from 1
+    pub column: usize,
+}
+
+impl From<Marker> for Position {
+    fn from(mark: Marker) -> Self {
+        Self {
+            line: mark.line(),
+            column: mark.col() + 1,
+        }
+    }
+}
+
+/// A container being walked while building a [`SourceMap`](SourceMap)
+enum Frame {
+    /// A mapping, along with the key whose value comes next, if any, and
+    /// whether its position should be moved to the next key
+    Mapping(Option<String>, bool),
+
+    /// A sequence, along with the index of the item that comes next
+    Sequence(usize),
+}
+
+/// Lookup table from document locations to source positions
+///
+/// Works for both YAML and JSON input, since JSON is (for all practical
+/// purposes) a subset of YAML.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    /// Positions of every node, keyed by unescaped JSON pointer segments
+    positions: HashMap<Vec<String>, Position>,
+}
+
+impl SourceMap {
+    /// Build a source map out of the text of a document
+    ///
+    /// Returns `None` if the text can't be parsed.
+    pub fn new(text: &str) -> Option<Self> {
+        let mut builder = Builder {
+            map: Self::default(),
+            stack: Vec::new(),
+        };
+
+        Parser::new(text.chars()).load(&mut builder, false).ok()?;
+
+        Some(builder.map)
+    }
+
+    /// Find the position of a location, or of its closest known ancestor
+    pub fn position(&self, location: &[String]) -> Option<Position> {
+        (0..=location.len())
+            .rev()
+            .filter_map(|len| location.get(..len))
+            .find_map(|x| self.positions.get(x))
+            .copied()
+    }
+}
+
+/// Event receiver that records the position of every node
+struct Builder {
+    /// The map being built
+    map: SourceMap,
+
+    /// The containers enclosing the current node, and their locations
+    stack: Vec<(Frame, Vec<String>)>,
+}
+
+impl Builder {
+    /// Work out the location of the node that starts at `mark`
+    ///
+    /// Returns `None` if the node is a mapping key rather than a value.
+    fn enter(&mut self, event: &Event, mark: Marker) -> Option<Vec<String>> {
+        let (frame, mut location) = match self.stack.last_mut() {
+            Some((frame, location)) => (Some(frame), location.clone()),
+            None => (None, Vec::new()),
+        };
+
+        match frame {
+            Some(Frame::Mapping(key @ None, first_key)) => {
+                if let Event::Scalar(name, ..) = event {
+                    *key = Some(name.clone());
+
+                    if *first_key {
+                        *first_key = false;
+                        self.map
+                            .positions
+                            .insert(location.clone(), mark.into());
+                    }
+
+                    // Point at the key rather than the value, since that's
+                    // where people expect the error to be
+                    location.push(name.clone());
+                    self.record(location, mark);
+                }
+
+                return None;
+            }
+            Some(Frame::Mapping(key @ Some(_), _)) => {
+                location.extend(key.take());
+            }
+            Some(Frame::Sequence(index)) => {
+                location.push(index.to_string());
+                *index += 1;
+            }
+            None => (),
+        }
+
+        self.record(location.clone(), mark);
+
+        Some(location)
+    }
+
+    /// Record a position unless one is already known for that location
+    fn record(&mut self, location: Vec<String>, mark: Marker) {
+        self.map.positions.entry(location).or_insert(mark.into());
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(..) | Event::Alias(_) => {
+                self.enter(&event, mark);
+            }
+            Event::MappingStart(_) | Event::SequenceStart(_) => {
+                // Complex mapping keys can't be expressed as JSON pointers, so
+                // their contents are attributed to the mapping itself
+                let location = self.enter(&event, mark).unwrap_or_else(|| {
+                    self.stack.last().map(|x| x.1.clone()).unwrap_or_default()
+                });
+
+                // Block mappings are marked where their first key ends, so
+                // unless the mapping is a value already located at its key,
+                // point at the first key instead
+                let frame = if matches!(event, Event::MappingStart(_)) {
+                    Frame::Mapping(
+                        None,
+                        self.map.positions.get(&location) == Some(&mark.into()),
+                    )
+                } else {
+                    Frame::Sequence(0)
+                };
+
+                self.stack.push((frame, location));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Position, SourceMap};
+
+    /// Look up the position of the location made of `segments`
+    fn position(map: &SourceMap, segments: &[&str]) -> Option<Position> {
+        let location: Vec<String> =
+            segments.iter().map(|x| (*x).to_owned()).collect();
+
+        map.position(&location)
+    }
+
+    /// Nested keys and sequence items in YAML point at where they start
+    #[test]
+    fn nested_yaml() {
+        let map = SourceMap::new(
+            "paths:\n  /items/{id}:\n    get:\n      parameters:\n        - \
+             name: id\n          in: path\n        - name: limit\n",
+        )
+        .unwrap_or_else(|| panic!("invalid YAML"));
+
+        assert_eq!(
+            position(&map, &["paths", "/items/{id}", "get"]),
+            Some(Position {
+                line: 3,
+                column: 5
+            })
+        );
+        assert_eq!(
+            position(&map, &["paths", "/items/{id}", "get", "parameters", "0"]),
+            Some(Position {
+                line: 5,
+                column: 11
+            })
+        );
+        assert_eq!(
+            position(
+                &map,
+                &["paths", "/items/{id}", "get", "parameters", "1", "name"]
+            ),
+            Some(Position {
+                line: 7,
+                column: 11
+            })
+        );
+    }
+
+    /// Nested keys and array items in JSON point at where they start
+    #[test]
+    fn nested_json() {
+        let map = SourceMap::new(
+            "{\n  \"a\": {\n    \"b\": [1, {\"c\": true}]\n  }\n}\n",
+        )
+        .unwrap_or_else(|| panic!("invalid JSON"));
+
+        assert_eq!(
+            position(&map, &["a", "b"]),
+            Some(Position {
+                line: 3,
+                column: 5
+            })
+        );
+        assert_eq!(
+            position(&map, &["a", "b", "1", "c"]),
+            Some(Position {
+                line: 3,
+                column: 15
+            })
+        );
+    }
+
+    /// Locations that aren't in the document fall back to their closest
+    /// ancestor that is
+    #[test]
+    fn closest_ancestor() {
+        let map = SourceMap::new("a:\n  b: 1\n")
+            .unwrap_or_else(|| panic!("invalid YAML"));
+
+        assert_eq!(
+            position(&map, &["a", "b", "c", "d"]),
+            Some(Position {
+                line: 2,
+                column: 3
+            })
+        );
+        assert_eq!(
+            position(&map, &["x"]),
+            Some(Position {
+                line: 1,
+                column: 1
+            })
+        );
+    }
+}
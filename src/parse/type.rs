@@ -9,6 +9,7 @@ use okapi::{
     openapi3::SchemaObject,
     schemars::schema::{InstanceType, Schema, SingleOrVec},
 };
+use serde_json::Value;
 
 use super::{Error, ErrorKind, Field, Struct};
 
@@ -40,12 +41,20 @@ pub enum Type {
     /// An optional [`Type`](Type)
     Option(Box<Self>),
 
+    /// Exactly one constant value
+    ///
+    /// This is always a string, number, boolean, or null.
+    Literal(Value),
+
     /// List of another [`Type`](Type)
     List(Box<Self>),
 
     /// Deduplicated list of another [`Type`](Type)
     Set(Box<Self>),
 
+    /// Fixed-length list where each position has its own [`Type`](Type)
+    Tuple(Vec<Self>),
+
     /// A collection of properties
     Struct(Struct),
 
@@ -59,13 +68,32 @@ impl From<Struct> for Type {
     }
 }
 
+impl TryFrom<&Schema> for Type {
+    type Error = Error;
+
+    /// The schema `true` allows any value, and `false` allows none
+    fn try_from(schema: &Schema) -> Result<Self, Self::Error> {
+        match schema {
+            Schema::Bool(true) => Ok(Self::Any),
+            Schema::Bool(false) => Err(ErrorKind::BooleanSchema.into()),
+            Schema::Object(x) => Self::try_from(x),
+        }
+    }
+}
+
 impl TryFrom<&SchemaObject> for Type {
     type Error = Error;
 
     fn try_from(schema_object: &SchemaObject) -> Result<Self, Self::Error> {
+        // JSON Schema 2020-12 spells nullable types as a list of types
+        if let Some(SingleOrVec::Vec(types)) = &schema_object.instance_type {
+            return Self::try_from_types(schema_object, types);
+        }
+
         // Possible data types:
         //
         // * [ ] enum?
+        // * [X] const
         // * [?] ref-to-object
         // * [X] array
         // * [X] boolean
@@ -75,17 +103,170 @@ impl TryFrom<&SchemaObject> for Type {
         // * [X] object
         // * [X] string
 
-        Self::try_from_ref(schema_object)
-            .or_else(|_| Self::try_from_object(schema_object))
-            .or_else(|_| Self::try_from_null(schema_object))
-            .or_else(|_| Self::try_from_string(schema_object))
-            .or_else(|_| Self::try_from_number(schema_object))
-            .or_else(|_| Self::try_from_integer(schema_object))
-            .or_else(|_| Self::try_from_boolean(schema_object))
-            .or_else(|_| Self::try_from_array(schema_object))
+        let parsers: [Parser; 9] = [
+            Self::try_from_ref,
+            Self::try_from_const,
+            Self::try_from_object,
+            Self::try_from_null,
+            Self::try_from_string,
+            Self::try_from_number,
+            Self::try_from_integer,
+            Self::try_from_boolean,
+            Self::try_from_array,
+        ];
+
+        let mut result = Err(ErrorKind::Unimplemented.into());
+
+        // Stop at the first parser that either succeeds or finds a real
+        // problem, rather than one that simply doesn't apply
+        for parser in parsers {
+            result = parser(schema_object);
+
+            if !matches!(&result, Err(e) if e.is_mismatch()) {
+                break;
+            }
+        }
+
+        // Nothing understood this schema, so try to say why
+        let r#type = result.map_err(|e| {
+            if e.is_mismatch() {
+                unsupported_keyword(schema_object).map_or(e, Error::unsupported)
+            } else {
+                e
+            }
+        })?;
+
+        Ok(if nullable(schema_object) {
+            r#type.optional()
+        } else {
+            r#type
+        })
     }
 }
 
+/// Find a keyword that would explain why a schema couldn't be converted
+fn unsupported_keyword(schema_object: &SchemaObject) -> Option<&'static str> {
+    if let Some(subschemas) = schema_object.subschemas.as_deref() {
+        if subschemas.all_of.is_some() {
+            return Some("allOf");
+        }
+
+        if subschemas.any_of.is_some() {
+            return Some("anyOf");
+        }
+
+        if subschemas.one_of.is_some() {
+            return Some("oneOf");
+        }
+
+        if subschemas.not.is_some() {
+            return Some("not");
+        }
+    }
+
+    if schema_object.enum_values.is_some() {
+        return Some("enum");
+    }
+
+    None
+}
+
+/// Whether a schema allows `null` through OpenAPI 3.0's `nullable` or the
+/// `x-nullable` extension used with Swagger 2.0
+fn nullable(schema_object: &SchemaObject) -> bool {
+    ["nullable", "x-nullable"]
+        .iter()
+        .any(|x| schema_object.extensions.get(*x) == Some(&Value::Bool(true)))
+}
+
+/// Collect the documentation for a schema
+///
+/// This is its title and description, followed by descriptions of its enum
+/// values, its examples, and a link to its external documentation. Examples
+/// may be given as an `examples` array (JSON Schema 2020-12) or a single
+/// `example` (OpenAPI 3.0).
+pub fn docs(schema_object: &SchemaObject) -> Option<String> {
+    let metadata = schema_object.metadata.as_deref();
+    let extensions = &schema_object.extensions;
+
+    let mut paragraphs: Vec<String> = [
+        metadata.and_then(|x| x.title.as_deref()),
+        metadata.and_then(|x| x.description.as_deref()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|x| !x.is_empty())
+    .map(ToOwned::to_owned)
+    .collect();
+
+    if let Some(values) = &schema_object.enum_values {
+        // Descriptions are given either in the same order as the values or
+        // keyed by the values
+        let descriptions = extensions
+            .get("x-enum-descriptions")
+            .or_else(|| extensions.get("x-enumDescriptions"));
+
+        let mut list = String::from("Values:\n");
+
+        for (i, value) in values.iter().enumerate() {
+            let description = descriptions.and_then(|x| match x {
+                Value::Array(xs) => xs.get(i),
+                Value::Object(xs) => xs.get(value.as_str()?),
+                _ => None,
+            });
+
+            match description.and_then(Value::as_str) {
+                Some(x) => list.push_str(&format!("\n* `{value}`: {x}")),
+                None => list.push_str(&format!("\n* `{value}`")),
+            }
+        }
+
+        paragraphs.push(list);
+    }
+
+    let examples: Vec<&Value> = metadata
+        .map(|x| x.examples.iter())
+        .into_iter()
+        .flatten()
+        .chain(extensions.get("example"))
+        .collect();
+
+    if !examples.is_empty() {
+        let mut list = String::from("Examples:\n");
+
+        for example in examples {
+            list.push_str(&format!("\n* `{example}`"));
+        }
+
+        paragraphs.push(list);
+    }
+
+    // Schemas don't have a field for this, so it's left in the extensions
+    if let Some(external_docs) = extensions.get("externalDocs") {
+        if let Some(url) = external_docs.get("url").and_then(Value::as_str) {
+            let description =
+                external_docs.get("description").and_then(Value::as_str);
+
+            paragraphs.push(see_also(url, description));
+        }
+    }
+
+    (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n"))
+}
+
+/// Describe a link to external documentation
+pub fn see_also(url: &str, description: Option<&str>) -> String {
+    match description {
+        Some(description) => format!("See also: {description} <{url}>"),
+        None => format!("See also: <{url}>"),
+    }
+}
+
+/// A function that tries to convert a [`SchemaObject`](SchemaObject) into one
+/// particular kind of [`Type`](Type)
+type Parser = fn(&SchemaObject) -> Result<Type, Error>;
+
 /// Generate a simple conversion function from [`SchemaObject`](SchemaObject)s
 /// to [`Type`](Type)s
 macro_rules! try_from_simple {
@@ -103,9 +284,10 @@ macro_rules! try_from_simple {
                     .as_ref()
                     .ok_or(ErrorKind::Unimplemented)?;
 
-                let instance_type = match single_or_vec {
-                    SingleOrVec::Single(x) => x,
-                    _ => return Err(ErrorKind::Unimplemented.into()),
+                let SingleOrVec::Single(instance_type) = single_or_vec else {
+                    return Err(
+                        Error::unsupported("type")
+                    );
                 };
 
                 match instance_type.as_ref() {
@@ -126,12 +308,92 @@ impl Type {
         try_from_null = Null => None,
     }
 
+    /// Wrap this type in [`Type::Option`](Type::Option) unless it already is
+    #[must_use]
+    pub fn optional(self) -> Self {
+        match self {
+            Self::Option(_) => self,
+            _ => Self::Option(Box::new(self)),
+        }
+    }
+
+    /// Names of the component schemas this type refers to, including through
+    /// the types it contains
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Self::Ref(x) => {
+                vec![x.rsplit_once('/').map_or(x.as_str(), |(_, x)| x)]
+            }
+            Self::Option(x) | Self::List(x) | Self::Set(x) => x.references(),
+            Self::Tuple(xs) => xs.iter().flat_map(Self::references).collect(),
+            Self::Struct(x) => {
+                x.fields.values().flat_map(|x| x.r#type.references()).collect()
+            }
+            Self::String
+            | Self::None
+            | Self::Bool
+            | Self::Float
+            | Self::Integer
+            | Self::Any
+            | Self::Literal(_) => Vec::new(),
+        }
+    }
+
+    /// Try to convert a [`SchemaObject`](SchemaObject) with a list of types
+    ///
+    /// Only a single type, optionally alongside `null`, is supported.
+    fn try_from_types(
+        schema_object: &SchemaObject,
+        types: &[InstanceType],
+    ) -> Result<Self, Error> {
+        let nullable = types.contains(&InstanceType::Null);
+
+        let others: Vec<_> =
+            types.iter().filter(|x| **x != InstanceType::Null).collect();
+
+        let inner = match others.as_slice() {
+            [] if nullable => return Ok(Self::None),
+            [] => return Ok(Self::Any),
+            [other] => {
+                let mut schema_object = schema_object.clone();
+                schema_object.instance_type =
+                    Some(SingleOrVec::Single(Box::new(**other)));
+
+                Self::try_from(&schema_object)?
+            }
+            _ => return Err(Error::unsupported("type")),
+        };
+
+        Ok(if nullable {
+            inner.optional()
+        } else {
+            inner
+        })
+    }
+
+    /// Try to convert a [`SchemaObject`](SchemaObject) into a constant
+    fn try_from_const(schema_object: &SchemaObject) -> Result<Self, Error> {
+        match &schema_object.const_value {
+            None => Err(ErrorKind::OtherType.into()),
+            Some(Value::Array(_) | Value::Object(_)) => {
+                Err(Error::unsupported("const"))
+            }
+            Some(x) => Ok(Self::Literal(x.clone())),
+        }
+    }
+
     /// Try to convert a [`SchemaObject`](SchemaObject) into a ref-to-type
     fn try_from_ref(schema_object: &SchemaObject) -> Result<Self, Error> {
-        if let Some(reference) = schema_object.reference.as_deref() {
-            Ok(Self::Ref(reference.to_owned()))
-        } else {
-            Err(ErrorKind::OtherType.into())
+        let Some(reference) = schema_object.reference.as_deref() else {
+            return Err(ErrorKind::OtherType.into());
+        };
+
+        // Only local references to component schemas can be turned into names
+        match reference.strip_prefix("#/components/schemas/") {
+            Some(name) if !name.is_empty() && !name.contains('/') => {
+                Ok(Self::Ref(reference.to_owned()))
+            }
+            _ => Err(ErrorKind::InvalidReference(reference.to_owned()).into()),
         }
     }
 
@@ -147,11 +409,18 @@ impl Type {
                     .iter()
                     // TODO: use `Iterator::try_collect` instead when it stabilizes
                     .try_fold(BTreeMap::new(), |mut acc, (name, schema)| {
-                        let mut field = Field::try_from(schema)?;
+                        let mut field = Field::try_from(schema).map_err(|e| {
+                            e.within(["properties", name.as_str()])
+                        })?;
 
                         // If the field is optional, make it so
-                        if object_validation.required.get(name).is_none() {
-                            field.r#type = Self::Option(Box::new(field.r#type));
+                        // Fields with defaults always have a value
+                        if !object_validation.required.contains(name) {
+                            field.required = false;
+
+                            if field.default.is_none() {
+                                field.r#type = field.r#type.optional();
+                            }
                         }
 
                         acc.insert(name.clone(), field);
@@ -159,13 +428,7 @@ impl Type {
                     })?
                     .into();
 
-                if let Some(docs) = schema_object
-                    .metadata
-                    .as_ref()
-                    .and_then(|x| x.description.as_ref())
-                {
-                    // TODO: include more things like examples, the title, and
-                    // so on
+                if let Some(docs) = docs(schema_object) {
                     s.set_docs(docs);
                 }
 
@@ -198,10 +461,8 @@ impl Type {
             .as_ref()
             .ok_or(ErrorKind::Unimplemented)?;
 
-        let instance_type = if let SingleOrVec::Single(x) = single_or_vec {
-            x
-        } else {
-            return Err(ErrorKind::Unimplemented.into());
+        let SingleOrVec::Single(instance_type) = single_or_vec else {
+            return Err(Error::unsupported("type"));
         };
 
         match instance_type.as_ref() {
@@ -209,32 +470,75 @@ impl Type {
             _ => return Err(ErrorKind::OtherType.into()),
         }
 
-        let array_validation = match schema_object.array.as_ref() {
-            Some(x) => x,
+        // JSON Schema 2020-12 describes tuples with `prefixItems`
+        if let Some(prefix_items) = schema_object.extensions.get("prefixItems")
+        {
+            let closed = match schema_object
+                .array
+                .as_ref()
+                .and_then(|x| x.items.as_ref())
+            {
+                None => true,
+                Some(SingleOrVec::Single(x)) => {
+                    matches!(x.as_ref(), Schema::Bool(false))
+                }
+                Some(SingleOrVec::Vec(_)) => false,
+            };
 
-            // There's either no type information, or it's specified in a way we
-            // don't understand. So, we have to assume that it can be any valid
-            // JSON.
-            None => return Ok(Type::Any),
+            // Additional items can't be described by a tuple type
+            if !closed {
+                return Err(Error::unsupported("items"));
+            }
+
+            let Ok(schemas) =
+                serde_json::from_value::<Vec<Schema>>(prefix_items.clone())
+            else {
+                return Err(Error::unsupported("prefixItems"));
+            };
+
+            return Self::try_from_tuple(&schemas)
+                .map_err(|e| e.within(["prefixItems"]));
+        }
+
+        // There's either no type information, or it's specified in a way we
+        // don't understand. So, we have to assume that it can be any valid
+        // JSON.
+        let Some(array_validation) = schema_object.array.as_ref() else {
+            return Ok(Type::Any);
         };
 
         match &array_validation.items {
-            None | Some(SingleOrVec::Vec(_)) => {
-                Err(ErrorKind::Unimplemented.into())
+            None => Err(Error::unsupported("items")),
+
+            // Older drafts describe tuples with a list of `items`
+            Some(SingleOrVec::Vec(schemas)) => {
+                Self::try_from_tuple(schemas).map_err(|e| e.within(["items"]))
             }
-            Some(SingleOrVec::Single(schema)) => match schema.as_ref() {
-                Schema::Bool(_) => todo!(),
-                Schema::Object(schema_object) => {
-                    let inner = Self::try_from(schema_object)?;
+            Some(SingleOrVec::Single(schema)) => {
+                let inner = Self::try_from(schema.as_ref())
+                    .map_err(|e| e.within(["items"]))?;
 
-                    match array_validation.unique_items {
-                        Some(true) => Ok(Self::Set(Box::new(inner))),
+                match array_validation.unique_items {
+                    Some(true) => Ok(Self::Set(Box::new(inner))),
 
-                        // Assume unspecified means regular list
-                        None | Some(false) => Ok(Self::List(Box::new(inner))),
-                    }
+                    // Assume unspecified means regular list
+                    None | Some(false) => Ok(Self::List(Box::new(inner))),
                 }
-            },
+            }
         }
     }
+
+    /// Try to convert the schemas of each position in a tuple
+    ///
+    /// Errors are located relative to the list of schemas.
+    fn try_from_tuple(schemas: &[Schema]) -> Result<Self, Error> {
+        schemas
+            .iter()
+            .enumerate()
+            .map(|(i, schema)| {
+                Self::try_from(schema).map_err(|e| e.within([i.to_string()]))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::Tuple)
+    }
 }
@@ -0,0 +1,761 @@
+//! Convert Swagger 2.0 documents to OpenAPI 3.0
+//!
+//! This works on the document before it's deserialized, since the two versions
+//! are shaped too differently to share types. Global parameters and responses
+//! are copied to where they're used rather than moved into `components`,
+//! because body and form parameters have to become request bodies.
+
+use serde_json::{json, Map, Value};
+
+/// HTTP methods that can appear in a path item
+const METHODS: [&str; 7] =
+    ["get", "put", "post", "delete", "options", "head", "patch"];
+
+/// Media types a Swagger 2.0 document is assumed to use if it doesn't say
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+
+/// Whether a document is written in Swagger 2.0
+pub fn is_swagger(document: &Value) -> bool {
+    document
+        .get("swagger")
+        .and_then(Value::as_str)
+        .is_some_and(|x| x.starts_with("2."))
+}
+
+/// Convert a Swagger 2.0 document to an equivalent OpenAPI 3.0 document
+pub fn convert(swagger: &Value) -> Value {
+    let mut openapi = Map::new();
+
+    openapi.insert("openapi".to_owned(), json!("3.0.3"));
+
+    for (key, value) in entries(swagger) {
+        if matches!(key.as_str(), "info" | "tags" | "externalDocs" | "security")
+            || key.starts_with("x-")
+        {
+            openapi.insert(key.clone(), value.clone());
+        }
+    }
+
+    let servers = servers(swagger);
+    if !servers.is_empty() {
+        openapi.insert("servers".to_owned(), Value::Array(servers));
+    }
+
+    openapi.insert(
+        "paths".to_owned(),
+        Value::Object(
+            entries(swagger.get("paths"))
+                .map(|(path, item)| (path.clone(), path_item(swagger, item)))
+                .collect(),
+        ),
+    );
+
+    let mut components = Map::new();
+
+    if let Some(definitions) = swagger.get("definitions") {
+        components.insert(
+            "schemas".to_owned(),
+            Value::Object(
+                entries(definitions)
+                    .map(|(name, x)| (name.clone(), schema(x)))
+                    .collect(),
+            ),
+        );
+    }
+
+    if let Some(definitions) = swagger.get("securityDefinitions") {
+        components.insert(
+            "securitySchemes".to_owned(),
+            Value::Object(
+                entries(definitions)
+                    .map(|(name, x)| (name.clone(), security_scheme(x)))
+                    .collect(),
+            ),
+        );
+    }
+
+    if !components.is_empty() {
+        openapi.insert("components".to_owned(), Value::Object(components));
+    }
+
+    let mut openapi = Value::Object(openapi);
+    rewrite_references(&mut openapi);
+
+    openapi
+}
+
+/// Iterate over the entries of a value if it's an object
+fn entries<'a, V>(value: V) -> impl Iterator<Item = (&'a String, &'a Value)>
+where
+    V: Into<Option<&'a Value>>,
+{
+    value.into().and_then(Value::as_object).into_iter().flatten()
+}
+
+/// Get a list of strings from a value, if it is one
+fn strings(value: Option<&Value>) -> Option<Vec<String>> {
+    value.and_then(Value::as_array).map(|x| {
+        x.iter().filter_map(Value::as_str).map(ToOwned::to_owned).collect()
+    })
+}
+
+/// Replace a reference to a global parameter or response with its target
+fn resolve<'a>(swagger: &'a Value, value: &'a Value) -> &'a Value {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|x| x.strip_prefix('#'))
+        .filter(|x| {
+            x.starts_with("/parameters/") || x.starts_with("/responses/")
+        })
+        .and_then(|x| swagger.pointer(x))
+        .unwrap_or(value)
+}
+
+/// Build the server list from `host`, `basePath`, and `schemes`
+fn servers(swagger: &Value) -> Vec<Value> {
+    let host = swagger.get("host").and_then(Value::as_str);
+    let base_path =
+        swagger.get("basePath").and_then(Value::as_str).unwrap_or_default();
+
+    match host {
+        Some(host) => strings(swagger.get("schemes"))
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| vec!["https".to_owned()])
+            .into_iter()
+            .map(|scheme| {
+                json!({ "url": format!("{scheme}://{host}{base_path}") })
+            })
+            .collect(),
+
+        // Without a host, URLs are relative to wherever the document is served
+        None if !base_path.is_empty() => vec![json!({ "url": base_path })],
+        None => Vec::new(),
+    }
+}
+
+/// Convert a path item
+///
+/// Body and form parameters shared by every operation are moved into each
+/// operation, since they become part of its request body.
+fn path_item(swagger: &Value, item: &Value) -> Value {
+    let shared: Vec<&Value> = item
+        .get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|x| resolve(swagger, x))
+        .collect();
+
+    let mut converted = Map::new();
+
+    for (key, value) in entries(item) {
+        if METHODS.contains(&key.as_str()) {
+            converted.insert(key.clone(), operation(swagger, value, &shared));
+        } else if key == "$ref" || key.starts_with("x-") {
+            converted.insert(key.clone(), value.clone());
+        }
+    }
+
+    let parameters: Vec<Value> =
+        shared.iter().filter(|x| !is_body(x)).map(|x| parameter(x)).collect();
+
+    if !parameters.is_empty() {
+        converted.insert("parameters".to_owned(), Value::Array(parameters));
+    }
+
+    Value::Object(converted)
+}
+
+/// Whether a parameter becomes part of the request body
+fn is_body(parameter: &Value) -> bool {
+    matches!(
+        parameter.get("in").and_then(Value::as_str),
+        Some("body" | "formData")
+    )
+}
+
+/// The name and location identifying a parameter
+fn parameter_key(parameter: &Value) -> (Option<&Value>, Option<&Value>) {
+    (parameter.get("name"), parameter.get("in"))
+}
+
+/// Convert an operation
+///
+/// `shared` are the parameters of the path item the operation belongs to.
+fn operation(swagger: &Value, operation: &Value, shared: &[&Value]) -> Value {
+    let consumes = strings(operation.get("consumes"))
+        .or_else(|| strings(swagger.get("consumes")))
+        .unwrap_or_default();
+    let produces = strings(operation.get("produces"))
+        .or_else(|| strings(swagger.get("produces")))
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_MEDIA_TYPE.to_owned()]);
+
+    let own: Vec<&Value> = operation
+        .get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|x| resolve(swagger, x))
+        .collect();
+
+    // Operations can override shared parameters, but shared non-body ones
+    // stay on the path item
+    let inherited = shared.iter().copied().filter(|x| {
+        is_body(x) && !own.iter().any(|y| parameter_key(x) == parameter_key(y))
+    });
+
+    let parameters: Vec<&Value> =
+        own.iter().copied().chain(inherited).collect();
+
+    let mut converted = Map::new();
+
+    for (key, value) in entries(operation) {
+        if matches!(
+            key.as_str(),
+            "tags"
+                | "summary"
+                | "description"
+                | "externalDocs"
+                | "operationId"
+                | "deprecated"
+                | "security"
+        ) || key.starts_with("x-")
+        {
+            converted.insert(key.clone(), value.clone());
+        }
+    }
+
+    let others: Vec<Value> = parameters
+        .iter()
+        .filter(|x| !is_body(x))
+        .map(|x| parameter(x))
+        .collect();
+
+    if !others.is_empty() {
+        converted.insert("parameters".to_owned(), Value::Array(others));
+    }
+
+    if let Some(body) = request_body(&parameters, &consumes) {
+        converted.insert("requestBody".to_owned(), body);
+    }
+
+    converted.insert(
+        "responses".to_owned(),
+        Value::Object(
+            entries(operation.get("responses"))
+                .map(|(code, x)| {
+                    (code.clone(), response(resolve(swagger, x), &produces))
+                })
+                .collect(),
+        ),
+    );
+
+    Value::Object(converted)
+}
+
+/// Build a request body out of body and form parameters
+fn request_body(parameters: &[&Value], consumes: &[String]) -> Option<Value> {
+    if let Some(body) = parameters
+        .iter()
+        .find(|x| x.get("in").and_then(Value::as_str) == Some("body"))
+    {
+        let consumes = if consumes.is_empty() {
+            vec![DEFAULT_MEDIA_TYPE.to_owned()]
+        } else {
+            consumes.to_owned()
+        };
+
+        let schema = body.get("schema").map_or_else(|| json!({}), schema);
+
+        let mut converted = Map::new();
+        copy(body, &mut converted, &["description", "required"]);
+        converted.insert(
+            "content".to_owned(),
+            Value::Object(
+                consumes
+                    .into_iter()
+                    .map(|x| (x, json!({ "schema": schema })))
+                    .collect(),
+            ),
+        );
+
+        return Some(Value::Object(converted));
+    }
+
+    let form: Vec<&Value> = parameters
+        .iter()
+        .copied()
+        .filter(|x| x.get("in").and_then(Value::as_str) == Some("formData"))
+        .collect();
+
+    if form.is_empty() {
+        return None;
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for parameter in &form {
+        let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+
+        let mut schema = simple_schema(parameter);
+        if let (Value::Object(schema), Some(description)) =
+            (&mut schema, parameter.get("description"))
+        {
+            schema.insert("description".to_owned(), description.clone());
+        }
+
+        properties.insert(name.to_owned(), schema);
+
+        if parameter.get("required").and_then(Value::as_bool) == Some(true) {
+            required.push(json!(name));
+        }
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+
+    let form_types: Vec<String> = consumes
+        .iter()
+        .filter(|x| {
+            x.starts_with("application/x-www-form-urlencoded")
+                || x.starts_with("multipart/form-data")
+        })
+        .cloned()
+        .collect();
+
+    // Files can only be uploaded as multipart
+    let form_types = if !form_types.is_empty() {
+        form_types
+    } else if form
+        .iter()
+        .any(|x| x.get("type").and_then(Value::as_str) == Some("file"))
+    {
+        vec!["multipart/form-data".to_owned()]
+    } else {
+        vec!["application/x-www-form-urlencoded".to_owned()]
+    };
+
+    Some(json!({
+        "required": form.iter().any(|x| {
+            x.get("required").and_then(Value::as_bool) == Some(true)
+        }),
+        "content": form_types
+            .into_iter()
+            .map(|x| (x, json!({ "schema": schema })))
+            .collect::<Map<_, _>>(),
+    }))
+}
+
+/// Convert a parameter that isn't part of the request body
+fn parameter(parameter: &Value) -> Value {
+    // References to other files can't be resolved here
+    if parameter.get("$ref").is_some() {
+        return parameter.clone();
+    }
+
+    let mut converted = Map::new();
+    copy(
+        parameter,
+        &mut converted,
+        &["name", "in", "description", "required", "allowEmptyValue"],
+    );
+
+    for (key, value) in entries(parameter) {
+        if key.starts_with("x-") {
+            converted.insert(key.clone(), value.clone());
+        }
+    }
+
+    if parameter.get("type").and_then(Value::as_str) == Some("array") {
+        let location = parameter.get("in").and_then(Value::as_str);
+        let collection_format =
+            parameter.get("collectionFormat").and_then(Value::as_str);
+
+        let (style, explode) = match (collection_format, location) {
+            (Some("multi"), _) => ("form", true),
+            (Some("ssv"), _) => ("spaceDelimited", false),
+            (Some("pipes"), _) => ("pipeDelimited", false),
+            (_, Some("query" | "cookie")) => ("form", false),
+            _ => ("simple", false),
+        };
+
+        converted.insert("style".to_owned(), json!(style));
+        converted.insert("explode".to_owned(), json!(explode));
+    }
+
+    converted.insert("schema".to_owned(), simple_schema(parameter));
+
+    Value::Object(converted)
+}
+
+/// Convert a response
+fn response(response: &Value, produces: &[String]) -> Value {
+    if response.get("$ref").is_some() {
+        return response.clone();
+    }
+
+    let mut converted = Map::new();
+
+    converted.insert(
+        "description".to_owned(),
+        response.get("description").cloned().unwrap_or_else(|| json!("")),
+    );
+
+    for (key, value) in entries(response) {
+        if key.starts_with("x-") {
+            converted.insert(key.clone(), value.clone());
+        }
+    }
+
+    if let Some(headers) = response.get("headers") {
+        converted.insert(
+            "headers".to_owned(),
+            Value::Object(
+                entries(headers)
+                    .map(|(name, header)| {
+                        let mut converted = Map::new();
+                        copy(header, &mut converted, &["description"]);
+                        converted
+                            .insert("schema".to_owned(), simple_schema(header));
+
+                        (name.clone(), Value::Object(converted))
+                    })
+                    .collect(),
+            ),
+        );
+    }
+
+    if let Some(schema) = response.get("schema").map(schema) {
+        let examples = response.get("examples");
+
+        converted.insert(
+            "content".to_owned(),
+            Value::Object(
+                produces
+                    .iter()
+                    .map(|media_type| {
+                        let mut content = json!({ "schema": schema });
+
+                        if let Some(example) =
+                            examples.and_then(|x| x.get(media_type))
+                        {
+                            content["example"] = example.clone();
+                        }
+
+                        (media_type.clone(), content)
+                    })
+                    .collect(),
+            ),
+        );
+    }
+
+    Value::Object(converted)
+}
+
+/// Convert the inline type information of a parameter, header, or `items`
+/// into a schema
+fn simple_schema(value: &Value) -> Value {
+    let mut schema = Map::new();
+
+    for (key, value) in entries(value) {
+        match key.as_str() {
+            "name" | "in" | "description" | "required" | "allowEmptyValue"
+            | "collectionFormat" => (),
+            "items" => {
+                schema.insert(key.clone(), simple_schema(value));
+            }
+            _ if key.starts_with("x-") => (),
+            _ => {
+                schema.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    let mut schema = Value::Object(schema);
+    file_to_binary(&mut schema);
+
+    schema
+}
+
+/// Convert a schema object
+fn schema(value: &Value) -> Value {
+    let mut schema = value.clone();
+    convert_schema(&mut schema);
+
+    schema
+}
+
+/// Convert a schema object in place
+fn convert_schema(schema: &mut Value) {
+    file_to_binary(schema);
+
+    let Value::Object(object) = schema else {
+        return;
+    };
+
+    // The discriminator used to be just the name of the property
+    if let Some(Value::String(property)) = object.get("discriminator") {
+        let discriminator = json!({ "propertyName": property });
+        object.insert("discriminator".to_owned(), discriminator);
+    }
+
+    for (key, value) in object.iter_mut() {
+        match key.as_str() {
+            "properties" => {
+                if let Value::Object(properties) = value {
+                    properties.values_mut().for_each(convert_schema);
+                }
+            }
+            "allOf" => {
+                if let Value::Array(schemas) = value {
+                    schemas.iter_mut().for_each(convert_schema);
+                }
+            }
+            "items" | "additionalProperties" => convert_schema(value),
+            _ => (),
+        }
+    }
+}
+
+/// Replace the `file` type, which no longer exists, with binary strings
+fn file_to_binary(schema: &mut Value) {
+    if schema.get("type").and_then(Value::as_str) == Some("file") {
+        schema["type"] = json!("string");
+        schema["format"] = json!("binary");
+    }
+}
+
+/// Convert a security definition into a security scheme
+fn security_scheme(definition: &Value) -> Value {
+    let mut converted = Map::new();
+    copy(definition, &mut converted, &["description"]);
+
+    for (key, value) in entries(definition) {
+        if key.starts_with("x-") {
+            converted.insert(key.clone(), value.clone());
+        }
+    }
+
+    match definition.get("type").and_then(Value::as_str) {
+        Some("basic") => {
+            converted.insert("type".to_owned(), json!("http"));
+            converted.insert("scheme".to_owned(), json!("basic"));
+        }
+        Some("oauth2") => {
+            let flow = match definition.get("flow").and_then(Value::as_str) {
+                Some("password") => "password",
+                Some("application") => "clientCredentials",
+                Some("accessCode") => "authorizationCode",
+                _ => "implicit",
+            };
+
+            let mut converted_flow = Map::new();
+            copy(
+                definition,
+                &mut converted_flow,
+                &["authorizationUrl", "tokenUrl"],
+            );
+            converted_flow.insert(
+                "scopes".to_owned(),
+                definition.get("scopes").cloned().unwrap_or_else(|| json!({})),
+            );
+
+            converted.insert("type".to_owned(), json!("oauth2"));
+            converted
+                .insert("flows".to_owned(), json!({ flow: converted_flow }));
+        }
+        _ => copy(definition, &mut converted, &["type", "name", "in"]),
+    }
+
+    Value::Object(converted)
+}
+
+/// Copy the given keys from one object to another if they're present
+fn copy(from: &Value, to: &mut Map<String, Value>, keys: &[&str]) {
+    for key in keys {
+        if let Some(value) = from.get(*key) {
+            to.insert((*key).to_owned(), value.clone());
+        }
+    }
+}
+
+/// Point references to definitions at their new location
+fn rewrite_references(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Some(name) =
+                            reference.strip_prefix("#/definitions/")
+                        {
+                            *reference = format!("#/components/schemas/{name}");
+                        }
+                    }
+                    _ => rewrite_references(value),
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(rewrite_references),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::convert;
+
+    /// Convert a document with a single `post /items` operation
+    fn operation(operation: &Value) -> Value {
+        let converted = convert(&json!({
+            "swagger": "2.0",
+            "info": { "title": "Test", "version": "1.0" },
+            "paths": { "/items": { "post": operation } },
+        }));
+
+        converted["paths"]["/items"]["post"].clone()
+    }
+
+    /// Body parameters become a request body for each consumed media type
+    #[test]
+    fn converts_body_parameters() {
+        let converted = operation(&json!({
+            "consumes": ["application/json", "text/json"],
+            "parameters": [{
+                "name": "item",
+                "in": "body",
+                "required": true,
+                "schema": { "$ref": "#/definitions/Item" },
+            }],
+            "responses": { "204": { "description": "Created" } },
+        }));
+
+        let schema = json!({ "$ref": "#/components/schemas/Item" });
+        assert_eq!(
+            converted["requestBody"],
+            json!({
+                "required": true,
+                "content": {
+                    "application/json": { "schema": schema },
+                    "text/json": { "schema": schema },
+                },
+            })
+        );
+        assert!(converted.get("parameters").is_none());
+    }
+
+    /// Form parameters become the properties of an object schema, sent as
+    /// multipart if any of them is a file
+    #[test]
+    fn converts_form_parameters() {
+        let converted = operation(&json!({
+            "parameters": [
+                {
+                    "name": "name",
+                    "in": "formData",
+                    "type": "string",
+                    "description": "What it's called",
+                    "required": true,
+                },
+                { "name": "upload", "in": "formData", "type": "file" },
+            ],
+            "responses": {},
+        }));
+
+        assert_eq!(
+            converted["requestBody"],
+            json!({
+                "required": true,
+                "content": {
+                    "multipart/form-data": {
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "What it's called",
+                                },
+                                "upload": {
+                                    "type": "string",
+                                    "format": "binary",
+                                },
+                            },
+                            "required": ["name"],
+                        },
+                    },
+                },
+            })
+        );
+
+        let converted = operation(&json!({
+            "parameters": [{ "name": "a", "in": "formData", "type": "integer" }],
+            "responses": {},
+        }));
+
+        assert_eq!(
+            converted["requestBody"]["content"]
+                ["application/x-www-form-urlencoded"]["schema"]["properties"]
+                ["a"],
+            json!({ "type": "integer" })
+        );
+    }
+
+    /// Collection formats become the equivalent style and explode settings
+    #[test]
+    fn converts_collection_formats() {
+        let parameter = |location: &str, format: Option<&str>| {
+            let mut parameter = json!({
+                "name": "ids",
+                "in": location,
+                "type": "array",
+                "items": { "type": "integer" },
+            });
+            if let Some(format) = format {
+                parameter["collectionFormat"] = json!(format);
+            }
+
+            let converted = operation(&json!({
+                "parameters": [parameter],
+                "responses": {},
+            }));
+            let parameter = &converted["parameters"][0];
+
+            assert_eq!(
+                parameter["schema"],
+                json!({ "type": "array", "items": { "type": "integer" } })
+            );
+            (parameter["style"].clone(), parameter["explode"].clone())
+        };
+
+        assert_eq!(
+            parameter("query", Some("multi")),
+            (json!("form"), json!(true))
+        );
+        assert_eq!(
+            parameter("query", Some("csv")),
+            (json!("form"), json!(false))
+        );
+        assert_eq!(parameter("query", None), (json!("form"), json!(false)));
+        assert_eq!(
+            parameter("query", Some("ssv")),
+            (json!("spaceDelimited"), json!(false))
+        );
+        assert_eq!(
+            parameter("query", Some("pipes")),
+            (json!("pipeDelimited"), json!(false))
+        );
+        assert_eq!(
+            parameter("header", Some("csv")),
+            (json!("simple"), json!(false))
+        );
+    }
+}